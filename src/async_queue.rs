@@ -1,13 +1,15 @@
+use crate::{Notified, Notify};
 use futures_lite::prelude::*;
 use std::collections::VecDeque;
 use std::pin::Pin;
-use std::task::{Context, Poll, Waker};
+use std::task::{Context, Poll};
 
 pin_project_lite::pin_project! {
     #[derive(Debug)]
     pub struct AsyncQueue<T> {
         queue: VecDeque<T>,
-        waker: Option<Waker>,
+        notify: Notify,
+        notified: Option<Notified>,
         capacity: usize,
     }
 }
@@ -24,7 +26,8 @@ impl<T> AsyncQueue<T> {
     pub fn new(capacity: usize) -> Self {
         Self {
             queue: VecDeque::with_capacity(1),
-            waker: None,
+            notify: Notify::new(),
+            notified: None,
             capacity,
         }
     }
@@ -33,18 +36,28 @@ impl<T> AsyncQueue<T> {
     pub fn enqueue(&mut self, value: T) -> Result<(), T> {
         if self.queue.len() < self.capacity {
             self.queue.push_back(value);
-            self.wake();
+            self.notify.notify_one();
             Ok(())
         } else {
             Err(value)
         }
     }
 
+    /// enqueue even when full by evicting the oldest value, which is returned.
+    #[inline]
+    pub fn force_enqueue(&mut self, value: T) -> Option<T> {
+        let evicted = match self.queue.len() < self.capacity {
+            true => None,
+            false => self.queue.pop_front(),
+        };
+        self.queue.push_back(value);
+        self.notify.notify_one();
+        evicted
+    }
+
     #[inline]
     pub fn wake(&mut self) {
-        if let Some(waker) = self.waker.take() {
-            waker.wake();
-        }
+        self.notify.notify_all();
     }
 }
 
@@ -54,11 +67,17 @@ impl<T> Stream for AsyncQueue<T> {
     #[inline]
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let proj = self.project();
-        match proj.queue.pop_front() {
-            Some(item) => Poll::Ready(Some(item)),
-            None => {
-                proj.waker.replace(cx.waker().clone());
-                Poll::Pending
+        loop {
+            if let Some(item) = proj.queue.pop_front() {
+                proj.notified.take();
+                return Poll::Ready(Some(item));
+            }
+            let notified = proj.notified.get_or_insert_with(|| proj.notify.notified());
+            match Pin::new(notified).poll(cx) {
+                Poll::Ready(()) => {
+                    proj.notified.take();
+                }
+                Poll::Pending => return Poll::Pending,
             }
         }
     }
@@ -97,4 +116,15 @@ mod test {
         assert!(q.enqueue(5).is_ok());
         assert!(q.enqueue(6).is_err());
     }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn force_enqueue() {
+        let mut q = AsyncQueue::new(2);
+        assert_eq!(q.force_enqueue(1), None);
+        assert_eq!(q.force_enqueue(2), None);
+        assert_eq!(q.force_enqueue(3), Some(1));
+        assert_eq!(q.next().await, Some(2));
+        assert_eq!(q.next().await, Some(3));
+    }
 }
@@ -54,6 +54,15 @@ impl<T> Sender<T> {
             Some(q) => q.borrow_mut().enqueue(value).map_err(|_| Error::Full),
         }
     }
+
+    /// send even when full by evicting the oldest value, which is returned.
+    #[inline]
+    pub fn force_send(&self, value: T) -> Result<Option<T>, Error> {
+        match self.0.upgrade() {
+            None => Err(Error::Closed),
+            Some(q) => Ok(q.borrow_mut().force_enqueue(value)),
+        }
+    }
 }
 
 impl<T> Stream for Receiver<T> {
@@ -144,4 +153,15 @@ mod test {
         assert!(tx.send(5).is_ok());
         assert!(tx.send(6).is_err());
     }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn force_send() {
+        let (tx, mut rx) = chan::<i32>(1);
+        assert_eq!(tx.force_send(1).ok(), Some(None));
+        assert_eq!(tx.force_send(2).ok(), Some(Some(1)));
+        assert_eq!(rx.next().await, Some(2));
+        drop(rx);
+        assert!(tx.force_send(3).is_err());
+    }
 }
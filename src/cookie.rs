@@ -0,0 +1,271 @@
+use crate::DomError;
+use crate::url::{percent_decode, percent_encode};
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    /// implies `Secure`, which browsers require for it.
+    None,
+}
+
+/// attributes for `Cookies::set` and `Cookies::remove`.
+/// without `expires` or `max_age`, the cookie ends with the browser session.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CookieOptions {
+    expires: Option<f64>,
+    max_age: Option<i64>,
+    path: Option<String>,
+    domain: Option<String>,
+    same_site: Option<SameSite>,
+    secure: bool,
+}
+
+impl CookieOptions {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// milliseconds since the Unix epoch, e.g. `js_sys::Date::now() + 86_400_000.0`.
+    #[inline]
+    pub fn expires(mut self, unix_millis: f64) -> Self {
+        self.expires.replace(unix_millis);
+        self
+    }
+
+    /// takes precedence over `expires` in browsers supporting it.
+    #[inline]
+    pub fn max_age(mut self, secs: i64) -> Self {
+        self.max_age.replace(secs);
+        self
+    }
+
+    /// defaults to the directory of the current path. "/" shares the cookie across the site.
+    #[inline]
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path.replace(path.into());
+        self
+    }
+
+    /// share the cookie with subdomains of `domain`.
+    #[inline]
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain.replace(domain.into());
+        self
+    }
+
+    #[inline]
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site.replace(same_site);
+        self
+    }
+
+    #[inline]
+    pub fn secure(mut self) -> Self {
+        self.secure = true;
+        self
+    }
+}
+
+/// the attributes, each with a leading "; ".
+impl std::fmt::Display for CookieOptions {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(expires) = self.expires {
+            write!(f, "; Expires={}", http_date(expires))?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={max_age}")?;
+        }
+        if let Some(path) = &self.path {
+            write!(f, "; Path={path}")?;
+        }
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={domain}")?;
+        }
+        match self.same_site {
+            Some(SameSite::Strict) => write!(f, "; SameSite=Strict")?,
+            Some(SameSite::Lax) => write!(f, "; SameSite=Lax")?,
+            Some(SameSite::None) => write!(f, "; SameSite=None")?,
+            None => {}
+        }
+        if self.secure || self.same_site == Some(SameSite::None) {
+            write!(f, "; Secure")?;
+        }
+        Ok(())
+    }
+}
+
+/// e.g. "Thu, 01 Jan 1970 00:00:00 GMT".
+#[inline]
+fn http_date(unix_millis: f64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let secs = (unix_millis / 1000.0).floor() as i64;
+    let (days, secs) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        // 1970-01-01 was a Thursday
+        WEEKDAYS[(days + 4).rem_euclid(7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// decoded name and value pairs of `document.cookie`.
+#[inline]
+fn parse(cookie: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    cookie.split(';').filter_map(|pair| {
+        let (name, value) = pair.split_once('=').unwrap_or(("", pair));
+        let name = name.trim();
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        match name.is_empty() && value.is_empty() {
+            true => None,
+            false => Some((percent_decode(name), percent_decode(value))),
+        }
+    })
+}
+
+/// `document.cookie`. names and values are percent-encoded when set and decoded when read.
+/// `HttpOnly` cookies are not visible.
+#[derive(Debug, Clone)]
+pub struct Cookies(pub(crate) web_sys::HtmlDocument);
+
+impl Cookies {
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.try_get(name).unwrap_throw()
+    }
+
+    /// when cookies with the same name exist for several paths, the one with the longest path.
+    #[inline]
+    pub fn try_get(&self, name: &str) -> Result<Option<String>, DomError> {
+        let cookie = self.0.cookie()?;
+        Ok(parse(&cookie).find_map(|(key, value)| (key == name).then_some(value)))
+    }
+
+    #[inline]
+    pub fn set(&self, name: &str, value: &str, options: &CookieOptions) {
+        self.try_set(name, value, options).unwrap_throw()
+    }
+
+    /// browsers silently ignore invalid cookies, e.g. `Secure` ones on plain http.
+    #[inline]
+    pub fn try_set(
+        &self,
+        name: &str,
+        value: &str,
+        options: &CookieOptions,
+    ) -> Result<(), DomError> {
+        let cookie = format!(
+            "{}={}{options}",
+            percent_encode(name),
+            percent_encode(value)
+        );
+        Ok(self.0.set_cookie(&cookie)?)
+    }
+
+    /// `options` needs the same `path` and `domain` the cookie was set with.
+    #[inline]
+    pub fn remove(&self, name: &str, options: &CookieOptions) {
+        self.try_remove(name, options).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_remove(&self, name: &str, options: &CookieOptions) -> Result<(), DomError> {
+        let options = CookieOptions {
+            expires: Some(0.0),
+            max_age: Some(0),
+            ..options.clone()
+        };
+        self.try_set(name, "", &options)
+    }
+
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = String> + use<> {
+        self.try_keys().unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_keys(&self) -> Result<impl Iterator<Item = String> + use<>, DomError> {
+        let cookie = self.0.cookie()?;
+        Ok(parse(&cookie)
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn parse_cookie() {
+        let cookie = r#"session=abc; theme="dark"; name=caf%C3%A9%3B; flag; a=b=c"#;
+        let cookies = parse(cookie).collect::<Vec<_>>();
+        assert_eq!(
+            cookies,
+            [
+                ("session".into(), "abc".into()),
+                ("theme".into(), "dark".into()),
+                ("name".into(), "café;".into()),
+                ("".into(), "flag".into()),
+                ("a".into(), "b=c".into()),
+            ]
+        );
+        assert_eq!(parse("").count(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn options() {
+        assert_eq!(CookieOptions::new().to_string(), "");
+        let options = CookieOptions::new()
+            .expires(1_445_412_480_000.0)
+            .max_age(3600)
+            .path("/")
+            .domain("example.com")
+            .same_site(SameSite::Lax)
+            .secure();
+        assert_eq!(
+            options.to_string(),
+            "; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=3600; Path=/; Domain=example.com; SameSite=Lax; Secure"
+        );
+        assert_eq!(
+            CookieOptions::new().same_site(SameSite::None).to_string(),
+            "; SameSite=None; Secure"
+        );
+        assert_eq!(http_date(0.0), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(
+            http_date(951_782_400_000.0),
+            "Tue, 29 Feb 2000 00:00:00 GMT"
+        );
+    }
+}
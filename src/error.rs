@@ -0,0 +1,70 @@
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomError {
+    name: String,
+    message: String,
+}
+
+impl DomError {
+    #[inline]
+    pub fn new(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+
+    /// name of the JS exception. e.g. "SyntaxError", "QuotaExceededError", "SecurityError"
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<JsValue> for DomError {
+    #[inline]
+    fn from(js_value: JsValue) -> Self {
+        // DOMException inherits from Error.prototype, so this covers both.
+        match js_value.dyn_ref::<js_sys::Error>() {
+            Some(err) => Self::new(err.name(), err.message()),
+            None => match js_value.as_string() {
+                Some(message) => Self::new("Error", message),
+                None => Self::new("Error", format!("{js_value:?}")),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for DomError {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)
+    }
+}
+
+impl std::error::Error for DomError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn from_js_value() {
+        let err = DomError::from(JsValue::from(js_sys::TypeError::new("bad")));
+        assert_eq!(err.name(), "TypeError");
+        assert_eq!(err.message(), "bad");
+        assert_eq!(err.to_string(), "TypeError: bad");
+
+        let err = DomError::from(JsValue::from_str("thrown string"));
+        assert_eq!(err.name(), "Error");
+        assert_eq!(err.message(), "thrown string");
+    }
+}
@@ -1,6 +1,8 @@
 use super::DomEvent;
 use wasm_bindgen::prelude::*;
 
+/// removes the listener when dropped.
+#[must_use = "dropping the binding removes the listener"]
 pub struct DomEventBinding<E: DomEvent> {
     pub(crate) target: web_sys::EventTarget,
     pub(crate) closure: Closure<dyn FnMut(web_sys::Event)>,
@@ -1,12 +1,20 @@
 use super::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
+type Predicate<T> = Box<dyn FnMut(&T) -> bool>;
+
 pub struct DomEventListenerBuilder<E: DomEvent> {
-    target: web_sys::EventTarget,
+    pub(crate) target: web_sys::EventTarget,
     _marker: std::marker::PhantomData<E>,
     capture: bool,
     passive: Option<bool>,
+    once: bool,
+    signal: Option<web_sys::AbortSignal>,
+    filter: Option<Predicate<E::WebSysEvent>>,
     prevent_default: bool,
+    prevent_default_if: Option<Predicate<E::WebSysEvent>>,
     stop_propagation: bool,
     stop_immediate_propagation: bool,
 }
@@ -19,7 +27,11 @@ impl<E: DomEvent> DomEventListenerBuilder<E> {
             _marker: std::marker::PhantomData,
             capture: false,
             passive: None,
+            once: false,
+            signal: None,
+            filter: None,
             prevent_default: false,
+            prevent_default_if: None,
             stop_propagation: false,
             stop_immediate_propagation: false,
         }
@@ -37,12 +49,53 @@ impl<E: DomEvent> DomEventListenerBuilder<E> {
         self
     }
 
+    /// handle only the first event passing `filter`. a stream ends after yielding it.
+    #[inline]
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    /// the listener is removed when `signal` is aborted, which also ends a stream. see `Scope::abort_signal`.
+    #[inline]
+    pub fn signal(mut self, signal: &web_sys::AbortSignal) -> Self {
+        self.signal.replace(signal.clone());
+        self
+    }
+
+    /// ignore events for which `predicate` returns false. other options do not apply to ignored events.
+    /// multiple filters must all pass.
+    #[inline]
+    pub fn filter(mut self, mut predicate: impl FnMut(&E::WebSysEvent) -> bool + 'static) -> Self {
+        self.filter = match self.filter.take() {
+            None => Some(Box::new(predicate)),
+            Some(mut previous) => Some(Box::new(move |event| previous(event) && predicate(event))),
+        };
+        self
+    }
+
+    /// also applies to events that fail to cast into `E::WebSysEvent`, e.g. a synthetic `new Event("click")`.
     #[inline]
     pub fn prevent_default(mut self) -> Self {
         self.prevent_default = true;
         self
     }
 
+    /// e.g. `.prevent_default_if(|e: &web_sys::KeyboardEvent| e.key() == "Enter")`.
+    /// multiple predicates are or-ed.
+    #[inline]
+    pub fn prevent_default_if(
+        mut self,
+        mut predicate: impl FnMut(&E::WebSysEvent) -> bool + 'static,
+    ) -> Self {
+        self.prevent_default_if = match self.prevent_default_if.take() {
+            None => Some(Box::new(predicate)),
+            Some(mut previous) => Some(Box::new(move |event| previous(event) || predicate(event))),
+        };
+        self
+    }
+
+    /// like `prevent_default`, also applies to events that fail to cast.
     #[inline]
     pub fn stop_propagation(mut self) -> Self {
         self.stop_propagation = true;
@@ -59,35 +112,85 @@ impl<E: DomEvent> DomEventListenerBuilder<E> {
     pub fn callback(
         self,
         mut callback: impl FnMut(E::WebSysEvent) + 'static,
+    ) -> DomEventBinding<E> {
+        self.bind(|_| Some(()), move |event, ()| callback(event))
+    }
+
+    /// buffers up to 16 events, dropping newer ones. see `into_stream_with`.
+    #[inline]
+    pub fn into_stream(self) -> DomEventStream<E> {
+        self.into_stream_with(DEFAULT_CAPACITY, BufferPolicy::DropNewest)
+    }
+
+    /// panics if `capacity` is 0, except for `BufferPolicy::Latest` and `BufferPolicy::Unbounded`.
+    #[inline]
+    #[track_caller]
+    pub fn into_stream_with(self, capacity: usize, policy: BufferPolicy) -> DomEventStream<E> {
+        self.bind_stream(capacity, policy, |_| Some(()), |event, ()| event)
+    }
+
+    /// `select` decides whether the event is handled and extracts extra data from it.
+    /// options such as `prevent_default` only apply to selected events.
+    #[inline]
+    pub(crate) fn bind<T>(
+        self,
+        mut select: impl FnMut(&web_sys::Event) -> Option<T> + 'static,
+        mut callback: impl FnMut(E::WebSysEvent, T) + 'static,
     ) -> DomEventBinding<E> {
         let Self {
             target,
             capture,
             passive,
+            once,
+            signal,
+            mut filter,
             prevent_default,
+            mut prevent_default_if,
             stop_propagation,
             stop_immediate_propagation,
             ..
         } = self;
 
+        let mut done = false;
         let closure = {
             Closure::new(move |event: web_sys::Event| {
-                if prevent_default {
-                    event.prevent_default();
+                if done {
+                    return;
                 }
-                if stop_propagation {
-                    event.stop_propagation();
-                }
-                if stop_immediate_propagation {
-                    event.stop_immediate_propagation();
-                }
-                match event.dyn_into::<E::WebSysEvent>() {
-                    Ok(event) => callback(event),
-                    Err(_) => crate::error!(
-                        "fail to JsCast::dyn_into::<{}>()",
+                let Some(selected) = select(&event) else {
+                    return;
+                };
+                let Some(typed) = E::cast(event.clone()) else {
+                    apply(
+                        &event,
+                        prevent_default,
+                        stop_propagation,
+                        stop_immediate_propagation,
+                    );
+                    crate::error!(
+                        "fail to cast \"{}\" event into {}",
+                        E::TYPE_STR,
                         std::any::type_name::<E::WebSysEvent>()
-                    ),
+                    );
+                    return;
                 };
+                if let Some(filter) = &mut filter
+                    && !filter(&typed)
+                {
+                    return;
+                }
+                done = once;
+                let prevent_default = prevent_default
+                    || prevent_default_if
+                        .as_mut()
+                        .is_some_and(|predicate| predicate(&typed));
+                apply(
+                    &event,
+                    prevent_default,
+                    stop_propagation,
+                    stop_immediate_propagation,
+                );
+                callback(typed, selected);
             })
         };
 
@@ -96,6 +199,9 @@ impl<E: DomEvent> DomEventListenerBuilder<E> {
         if let Some(passive) = passive {
             options.set_passive(passive);
         }
+        if let Some(signal) = &signal {
+            options.set_signal(signal);
+        }
 
         target
             .add_event_listener_with_callback_and_add_event_listener_options(
@@ -114,14 +220,181 @@ impl<E: DomEvent> DomEventListenerBuilder<E> {
     }
 
     #[inline]
-    pub fn into_stream(self) -> DomEventStream<E> {
-        let (tx, rx) = crate::chan(16);
-        let _binding = self.callback(move |event| {
-            if let Err(err) = tx.send(event) {
-                crate::error!("fail to send: {}", err.to_string());
+    #[track_caller]
+    pub(crate) fn bind_stream<T, U: 'static>(
+        self,
+        capacity: usize,
+        policy: BufferPolicy,
+        select: impl FnMut(&web_sys::Event) -> Option<T> + 'static,
+        mut map: impl FnMut(E::WebSysEvent, T) -> U + 'static,
+    ) -> DomEventStream<E, U> {
+        let (tx, rx) = crate::chan(policy.capacity(capacity));
+        let dropped = Rc::new(Cell::new(0));
+        let once = self.once;
+        let tx = Rc::new(RefCell::new(Some(tx)));
+        // end the stream once `signal` is aborted
+        let _abort = self.signal.as_ref().and_then(|signal| {
+            if signal.aborted() {
+                tx.borrow_mut().take();
+                return None;
+            }
+            let tx = tx.clone();
+            Some(
+                signal
+                    .make_listener::<AbortEvent>()
+                    .once()
+                    .callback(move |_| {
+                        tx.borrow_mut().take();
+                    }),
+            )
+        });
+        let _binding = self.bind(select, {
+            let dropped = dropped.clone();
+            move |event, selected| {
+                let mut tx = tx.borrow_mut();
+                let Some(sender) = &*tx else {
+                    return;
+                };
+                let value = map(event, selected);
+                let lost = match policy {
+                    BufferPolicy::DropNewest | BufferPolicy::Unbounded => {
+                        sender.send(value).is_err()
+                    }
+                    BufferPolicy::DropOldest | BufferPolicy::Latest => {
+                        !matches!(sender.force_send(value), Ok(None))
+                    }
+                };
+                if lost {
+                    dropped.set(dropped.get() + 1);
+                }
+                // closing the channel ends the stream
+                if once {
+                    tx.take();
+                }
             }
         });
 
-        DomEventStream { _binding, rx }
+        DomEventStream {
+            _binding,
+            _abort,
+            rx,
+            dropped,
+        }
+    }
+}
+
+#[inline]
+fn apply(
+    event: &web_sys::Event,
+    prevent_default: bool,
+    stop_propagation: bool,
+    stop_immediate_propagation: bool,
+) {
+    if prevent_default {
+        event.prevent_default();
+    }
+    if stop_propagation {
+        event.stop_propagation();
+    }
+    if stop_immediate_propagation {
+        event.stop_immediate_propagation();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Scope;
+    use wasm_bindgen_test::*;
+
+    type Click = ClickEvent<web_sys::Event>;
+
+    fn click(target: &web_sys::EventTarget, bubbles: bool, cancelable: bool) -> web_sys::Event {
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(bubbles);
+        init.set_cancelable(cancelable);
+        let event = web_sys::Event::new_with_event_init_dict("click", &init).unwrap();
+        target.dispatch_event(&event).unwrap();
+        event
+    }
+
+    fn counter() -> (Rc<Cell<u32>>, impl FnMut(web_sys::Event) + 'static) {
+        let count = Rc::new(Cell::new(0));
+        let f = {
+            let count = count.clone();
+            move |_| count.set(count.get() + 1)
+        };
+        (count, f)
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn once() {
+        let target = crate::document().create_element("div").unwrap();
+        let mut events = target
+            .make_listener::<Click>()
+            .filter(|event| event.bubbles())
+            .once()
+            .into_stream();
+        click(&target, false, true);
+        click(&target, true, true);
+        click(&target, true, false);
+        assert!(events.next().await.unwrap().cancelable());
+        assert!(events.next().await.is_none());
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn filter() {
+        let target = crate::document().create_element("div").unwrap();
+        let (count, f) = counter();
+        let _binding = target
+            .make_listener::<Click>()
+            .filter(|event| event.bubbles())
+            .filter(|event| event.cancelable())
+            .callback(f);
+        click(&target, true, false);
+        click(&target, false, true);
+        assert_eq!(count.get(), 0);
+        click(&target, true, true);
+        assert_eq!(count.get(), 1);
+
+        let _binding = target
+            .make_listener::<Click>()
+            .prevent_default_if(|event| event.bubbles())
+            .callback(|_| ());
+        assert!(click(&target, true, true).default_prevented());
+        assert!(!click(&target, false, true).default_prevented());
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn signal() {
+        let target = crate::document().create_element("div").unwrap();
+        let scope = Scope::new();
+        let (count, f) = counter();
+        let _binding = target
+            .make_listener::<Click>()
+            .signal(&scope.abort_signal())
+            .callback(f);
+        let mut events = target
+            .make_listener::<Click>()
+            .signal(&scope.abort_signal())
+            .into_stream();
+        click(&target, false, false);
+        assert_eq!(count.get(), 1);
+        scope.cancel("done");
+        click(&target, false, false);
+        assert_eq!(count.get(), 1);
+        // the event before the abort, then the end
+        assert!(events.next().await.is_some());
+        assert!(events.next().await.is_none());
+
+        // already aborted
+        let mut events = target
+            .make_listener::<Click>()
+            .signal(&scope.abort_signal())
+            .into_stream();
+        assert!(events.next().await.is_none());
     }
 }
@@ -0,0 +1,253 @@
+use super::DomEvent;
+use slab::Slab;
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+thread_local! {
+    // custom events being dispatched and their payloads, keyed by the event's `detail`
+    static PAYLOADS: RefCell<Slab<(web_sys::CustomEvent, Rc<dyn Any>)>> = RefCell::new(Slab::new());
+}
+
+/// a custom DOM event type carrying a Rust payload.
+///
+/// ```ignore
+/// struct CartUpdated;
+/// impl CustomDomEvent for CartUpdated {
+///     const TYPE_STR: &'static str = "cart-updated";
+///     type Payload = Vec<u32>;
+/// }
+/// node.make_custom_event::<CartUpdated>(vec![1, 2]).bubbles().dispatch();
+/// ```
+pub trait CustomDomEvent: Unpin + 'static {
+    const TYPE_STR: &'static str;
+    type Payload: 'static;
+}
+
+impl<E: CustomDomEvent> DomEvent for E {
+    const TYPE_STR: &'static str = <E as CustomDomEvent>::TYPE_STR;
+    type WebSysEvent = CustomEvent<E::Payload>;
+
+    #[inline]
+    fn cast(event: web_sys::Event) -> Option<Self::WebSysEvent> {
+        let event = event.dyn_into::<web_sys::CustomEvent>().ok()?;
+        let key = event.detail().as_f64()? as usize;
+        let (dispatched, payload) =
+            PAYLOADS.with(|payloads| payloads.borrow().get(key).cloned())?;
+        // a foreign event with the same type and a numeric `detail` is not ours
+        if dispatched != event {
+            return None;
+        }
+        let payload = payload.downcast::<E::Payload>().ok()?;
+        Some(CustomEvent { event, payload })
+    }
+}
+
+/// payload is shared among all listeners of a dispatch.
+#[derive(Debug)]
+pub struct CustomEvent<P> {
+    event: web_sys::CustomEvent,
+    payload: Rc<P>,
+}
+
+impl<P> Clone for CustomEvent<P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            event: self.event.clone(),
+            payload: self.payload.clone(),
+        }
+    }
+}
+
+impl<P> CustomEvent<P> {
+    #[inline]
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    #[inline]
+    pub fn into_payload(self) -> Rc<P> {
+        self.payload
+    }
+}
+
+impl<P> std::ops::Deref for CustomEvent<P> {
+    type Target = web_sys::CustomEvent;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.event
+    }
+}
+
+pub struct CustomEventBuilder<E: CustomDomEvent> {
+    target: web_sys::EventTarget,
+    payload: E::Payload,
+    bubbles: bool,
+    cancelable: bool,
+    composed: bool,
+}
+
+impl<E: CustomDomEvent> CustomEventBuilder<E> {
+    #[inline]
+    pub(crate) fn new(target: web_sys::EventTarget, payload: E::Payload) -> Self {
+        Self {
+            target,
+            payload,
+            bubbles: false,
+            cancelable: false,
+            composed: false,
+        }
+    }
+
+    #[inline]
+    pub fn bubbles(mut self) -> Self {
+        self.bubbles = true;
+        self
+    }
+
+    #[inline]
+    pub fn cancelable(mut self) -> Self {
+        self.cancelable = true;
+        self
+    }
+
+    /// propagate across shadow DOM boundaries.
+    #[inline]
+    pub fn composed(mut self) -> Self {
+        self.composed = true;
+        self
+    }
+
+    /// dispatch synchronously. returns `false` if a listener called `prevent_default` on a cancelable event.
+    #[inline]
+    #[track_caller]
+    pub fn dispatch(self) -> bool {
+        let Self {
+            target,
+            payload,
+            bubbles,
+            cancelable,
+            composed,
+        } = self;
+
+        let key = PAYLOADS.with(|payloads| payloads.borrow().vacant_key());
+
+        let init = web_sys::CustomEventInit::new();
+        init.set_bubbles(bubbles);
+        init.set_cancelable(cancelable);
+        init.set_composed(composed);
+        init.set_detail(&JsValue::from_f64(key as f64));
+
+        let event =
+            web_sys::CustomEvent::new_with_event_init_dict(<E as CustomDomEvent>::TYPE_STR, &init)
+                .unwrap_throw();
+        PAYLOADS.with(|payloads| {
+            let inserted = payloads
+                .borrow_mut()
+                .insert((event.clone(), Rc::new(payload)));
+            debug_assert_eq!(inserted, key);
+        });
+        let result = target.dispatch_event(&event);
+
+        // listeners have cloned the payload during the dispatch
+        PAYLOADS.with(|payloads| payloads.borrow_mut().remove(key));
+
+        result.unwrap_throw()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::EventTargetExt;
+    use wasm_bindgen_test::*;
+
+    struct Outer;
+    impl CustomDomEvent for Outer {
+        const TYPE_STR: &'static str = "mozkit-test-outer";
+        type Payload = String;
+    }
+
+    struct Inner;
+    impl CustomDomEvent for Inner {
+        const TYPE_STR: &'static str = "mozkit-test-inner";
+        type Payload = u32;
+    }
+
+    fn pending() -> usize {
+        PAYLOADS.with(|payloads| payloads.borrow().len())
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn round_trip() {
+        let target = crate::document().create_element("div").unwrap();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let _binding = target.make_listener::<Outer>().callback({
+            let received = received.clone();
+            move |event| received.borrow_mut().push(event.payload().clone())
+        });
+        assert!(target.make_custom_event::<Outer>("hello".into()).dispatch());
+        assert_eq!(*received.borrow(), ["hello"]);
+        assert_eq!(pending(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn nested() {
+        let target = crate::document().create_element("div").unwrap();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let _inner = target.make_listener::<Inner>().callback({
+            let received = received.clone();
+            move |event| received.borrow_mut().push(event.payload().to_string())
+        });
+        let _outer = target.make_listener::<Outer>().callback({
+            let received = received.clone();
+            let target = target.clone();
+            move |event| {
+                target.make_custom_event::<Inner>(42).dispatch();
+                // the outer payload is still there after the nested dispatch
+                received.borrow_mut().push(event.payload().clone());
+                assert_eq!(pending(), 1);
+            }
+        });
+        target.make_custom_event::<Outer>("outer".into()).dispatch();
+        assert_eq!(*received.borrow(), ["42", "outer"]);
+        assert_eq!(pending(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn foreign() {
+        let target = crate::document().create_element("div").unwrap();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let _binding = target.make_listener::<Outer>().callback({
+            let received = received.clone();
+            move |event| received.borrow_mut().push(event.payload().clone())
+        });
+        let foreign = {
+            let target = target.clone();
+            move || {
+                // the same type and the `detail` key of a pending dispatch
+                let init = web_sys::CustomEventInit::new();
+                init.set_detail(&JsValue::from_f64(0.0));
+                let event =
+                    web_sys::CustomEvent::new_with_event_init_dict("mozkit-test-outer", &init)
+                        .unwrap();
+                target.dispatch_event(&event).unwrap();
+            }
+        };
+        foreign();
+        assert!(received.borrow().is_empty());
+
+        // while a dispatch with the same payload type is pending elsewhere
+        let other = crate::document().create_element("div").unwrap();
+        let _other = other.make_listener::<Outer>().callback(move |_| foreign());
+        other.make_custom_event::<Outer>("ours".into()).dispatch();
+        assert!(received.borrow().is_empty());
+        assert_eq!(pending(), 0);
+    }
+}
@@ -0,0 +1,184 @@
+use super::*;
+use crate::Node;
+use wasm_bindgen::prelude::*;
+
+/// yields the event together with the element matched by the selector.
+pub type DelegatedEventStream<E> =
+    DomEventStream<E, (<E as DomEvent>::WebSysEvent, Node<web_sys::Element>)>;
+
+/// a single listener on a container handling events of descendants matching a selector.
+/// see `EventTargetExt::make_delegated_listener`.
+pub struct DelegatedListenerBuilder<E: DomEvent> {
+    builder: DomEventListenerBuilder<E>,
+    selector: String,
+}
+
+impl<E: DomEvent> DelegatedListenerBuilder<E> {
+    #[inline]
+    pub(crate) fn new(builder: DomEventListenerBuilder<E>, selector: String) -> Self {
+        Self { builder, selector }
+    }
+
+    #[inline]
+    pub fn capture(mut self) -> Self {
+        self.builder = self.builder.capture();
+        self
+    }
+
+    #[inline]
+    pub fn passive(mut self, value: bool) -> Self {
+        self.builder = self.builder.passive(value);
+        self
+    }
+
+    #[inline]
+    pub fn once(mut self) -> Self {
+        self.builder = self.builder.once();
+        self
+    }
+
+    #[inline]
+    pub fn signal(mut self, signal: &web_sys::AbortSignal) -> Self {
+        self.builder = self.builder.signal(signal);
+        self
+    }
+
+    #[inline]
+    pub fn filter(mut self, predicate: impl FnMut(&E::WebSysEvent) -> bool + 'static) -> Self {
+        self.builder = self.builder.filter(predicate);
+        self
+    }
+
+    /// only applies to events matching the selector.
+    #[inline]
+    pub fn prevent_default(mut self) -> Self {
+        self.builder = self.builder.prevent_default();
+        self
+    }
+
+    #[inline]
+    pub fn prevent_default_if(
+        mut self,
+        predicate: impl FnMut(&E::WebSysEvent) -> bool + 'static,
+    ) -> Self {
+        self.builder = self.builder.prevent_default_if(predicate);
+        self
+    }
+
+    #[inline]
+    pub fn stop_propagation(mut self) -> Self {
+        self.builder = self.builder.stop_propagation();
+        self
+    }
+
+    #[inline]
+    pub fn stop_immediate_propagation(mut self) -> Self {
+        self.builder = self.builder.stop_immediate_propagation();
+        self
+    }
+
+    #[inline]
+    fn select(
+        container: web_sys::EventTarget,
+        selector: String,
+    ) -> impl FnMut(&web_sys::Event) -> Option<Node<web_sys::Element>> + 'static {
+        let container = container.dyn_into::<web_sys::Node>().ok();
+        move |event| {
+            let target = event.target()?.dyn_into::<web_sys::Node>().ok()?;
+            // e.g. text nodes
+            let target = match target.dyn_into::<web_sys::Element>() {
+                Ok(element) => element,
+                Err(node) => node.parent_element()?,
+            };
+            let matched = match target.closest(&selector) {
+                Ok(matched) => matched?,
+                Err(err) => {
+                    crate::error!("{}", crate::DomError::from(err));
+                    return None;
+                }
+            };
+            // the container itself is not a delegated descendant, even if it matches
+            match &container {
+                Some(container)
+                    if !container.contains(Some(&matched))
+                        || matched.unchecked_ref::<web_sys::Node>() == container =>
+                {
+                    None
+                }
+                _ => Some(Node(matched)),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn callback(
+        self,
+        callback: impl FnMut(E::WebSysEvent, Node<web_sys::Element>) + 'static,
+    ) -> DomEventBinding<E> {
+        let Self { builder, selector } = self;
+        let select = Self::select(builder.target.clone(), selector);
+        builder.bind(select, callback)
+    }
+
+    #[inline]
+    pub fn into_stream(self) -> DelegatedEventStream<E> {
+        self.into_stream_with(DEFAULT_CAPACITY, BufferPolicy::DropNewest)
+    }
+
+    /// see `DomEventListenerBuilder::into_stream_with`.
+    #[inline]
+    #[track_caller]
+    pub fn into_stream_with(
+        self,
+        capacity: usize,
+        policy: BufferPolicy,
+    ) -> DelegatedEventStream<E> {
+        let Self { builder, selector } = self;
+        let select = Self::select(builder.target.clone(), selector);
+        builder.bind_stream(capacity, policy, select, |event, matched| (event, matched))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    type Click = ClickEvent<web_sys::Event>;
+
+    fn click(target: &web_sys::EventTarget) {
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("click", &init).unwrap();
+        target.dispatch_event(&event).unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn select() {
+        let container = crate::document().create_element("ul").unwrap();
+        container.set_class_name("row");
+        container.set_inner_html(
+            r#"<li class="row" id="a"><span><b>nested</b></span></li><li id="outside">outside</li>"#,
+        );
+        let row = container.query_selector("#a").unwrap().unwrap();
+        let nested = container.query_selector("b").unwrap().unwrap();
+        let outside = container.query_selector("#outside").unwrap().unwrap();
+
+        let mut rows = container
+            .make_delegated_listener::<Click>(".row")
+            .into_stream();
+        // a nested descendant of a row, outside any row, the matching container, and the row itself
+        click(&nested);
+        click(&outside);
+        click(&container);
+        click(&row);
+
+        let (_, matched) = rows.next().await.unwrap();
+        assert_eq!(*matched, row);
+        let (_, matched) = rows.next().await.unwrap();
+        assert_eq!(*matched, row);
+        assert_eq!(rows.dropped(), 0);
+        assert!(futures_lite::future::poll_once(rows.next()).await.is_none());
+    }
+}
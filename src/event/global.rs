@@ -0,0 +1,189 @@
+use super::{
+    BufferPolicy, DomEvent, DomEventListenerBuilder, DomEventStream, EventTargetExt, OfflineEvent,
+    OnlineEvent, ResizeEvent, ScrollEvent, VisibilityChangeEvent,
+};
+use crate::Timeout;
+use futures_lite::prelude::*;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// e.g. `on_window::<PopStateEvent>().into_stream()`.
+#[inline]
+pub fn on_window<E: DomEvent>() -> DomEventListenerBuilder<E> {
+    crate::window().make_listener()
+}
+
+#[inline]
+pub fn on_document<E: DomEvent>() -> DomEventListenerBuilder<E> {
+    crate::document().make_listener()
+}
+
+/// a stream of a global value. yields the current value first, then the latest value after changes.
+pub struct GlobalStream<T> {
+    get: fn() -> T,
+    started: bool,
+    changes: Vec<Pin<Box<dyn Stream<Item = ()>>>>,
+}
+
+impl<T> GlobalStream<T> {
+    #[inline]
+    fn new(get: fn() -> T) -> Self {
+        Self {
+            get,
+            started: false,
+            changes: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn on<E: DomEvent>(mut self, listener: DomEventListenerBuilder<E>) -> Self {
+        let changes = listener.bind_stream(1, BufferPolicy::Latest, |_| Some(()), |_, ()| ());
+        self.changes.push(Box::pin(changes));
+        self
+    }
+}
+
+impl<T> Stream for GlobalStream<T> {
+    type Item = T;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if !std::mem::replace(&mut self.started, true) {
+            return Poll::Ready(Some((self.get)()));
+        }
+        let mut changed = false;
+        let mut ended = true;
+        for changes in &mut self.changes {
+            // poll every source so that coalesced changes do not wake us again
+            match changes.as_mut().poll_next(cx) {
+                Poll::Ready(Some(())) => {
+                    changed = true;
+                    ended = false;
+                }
+                Poll::Ready(None) => {}
+                Poll::Pending => ended = false,
+            }
+        }
+        match (changed, ended) {
+            (true, _) => Poll::Ready(Some((self.get)())),
+            (false, true) => Poll::Ready(None),
+            (false, false) => Poll::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WindowSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl WindowSize {
+    /// `innerWidth` and `innerHeight`.
+    #[inline]
+    pub fn current() -> Self {
+        let window = crate::window();
+        let get = |value: Result<wasm_bindgen::JsValue, _>| {
+            value.ok().and_then(|value| value.as_f64()).unwrap_or(0.0)
+        };
+        Self {
+            width: get(window.inner_width()),
+            height: get(window.inner_height()),
+        }
+    }
+}
+
+/// yields the current size first, then the size once resizing pauses.
+pub struct WindowSizeStream {
+    current: Option<WindowSize>,
+    events: DomEventStream<ResizeEvent, ()>,
+    debounce: i32,
+    timer: Option<Timeout>,
+}
+
+impl Stream for WindowSizeStream {
+    type Item = WindowSize;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        if let Some(current) = this.current.take() {
+            return Poll::Ready(Some(current));
+        }
+        loop {
+            match this.events.poll_next(cx) {
+                Poll::Ready(Some(())) => {
+                    this.timer.replace(Timeout::new(this.debounce));
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => break,
+            }
+        }
+        if let Some(timer) = &mut this.timer
+            && timer.poll(cx).is_ready()
+        {
+            this.timer.take();
+            return Poll::Ready(Some(WindowSize::current()));
+        }
+        Poll::Pending
+    }
+}
+
+/// debounced by 100 milliseconds. see `window_size_with`.
+#[inline]
+pub fn window_size() -> WindowSizeStream {
+    window_size_with(100)
+}
+
+#[inline]
+pub fn window_size_with(debounce_millisecs: i32) -> WindowSizeStream {
+    WindowSizeStream {
+        current: Some(WindowSize::current()),
+        events: on_window::<ResizeEvent>().bind_stream(
+            1,
+            BufferPolicy::Latest,
+            |_| Some(()),
+            |_, ()| (),
+        ),
+        debounce: debounce_millisecs,
+        timer: None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ScrollPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl ScrollPosition {
+    /// `scrollX` and `scrollY`.
+    #[inline]
+    pub fn current() -> Self {
+        let window = crate::window();
+        Self {
+            x: window.scroll_x().unwrap_or_default(),
+            y: window.scroll_y().unwrap_or_default(),
+        }
+    }
+}
+
+/// window scroll position.
+#[inline]
+pub fn scroll_position() -> GlobalStream<ScrollPosition> {
+    GlobalStream::new(ScrollPosition::current).on(on_window::<ScrollEvent>().passive(true))
+}
+
+/// `true` while the document is visible.
+#[inline]
+pub fn visibility() -> GlobalStream<bool> {
+    GlobalStream::new(|| !crate::document().hidden()).on(on_document::<VisibilityChangeEvent>())
+}
+
+/// `true` while the browser is online, as far as `navigator.onLine` can tell.
+#[inline]
+pub fn online_status() -> GlobalStream<bool> {
+    GlobalStream::new(|| crate::window().navigator().on_line())
+        .on(on_window::<OnlineEvent>())
+        .on(on_window::<OfflineEvent>())
+}
@@ -1,22 +1,57 @@
 mod binding;
 mod builder;
+mod custom;
+mod delegate;
+mod global;
+mod outside;
 mod stream;
 
 pub use binding::DomEventBinding;
 pub use builder::DomEventListenerBuilder;
-pub use stream::DomEventStream;
+pub use custom::{CustomDomEvent, CustomEvent, CustomEventBuilder};
+pub use delegate::{DelegatedEventStream, DelegatedListenerBuilder};
+pub use global::{
+    GlobalStream, ScrollPosition, WindowSize, WindowSizeStream, on_document, on_window,
+    online_status, scroll_position, visibility, window_size, window_size_with,
+};
+pub use outside::OutsideEventStream;
+use stream::DEFAULT_CAPACITY;
+pub use stream::{BufferPolicy, DomEventStream};
 
-use crate::StreamSpawnExt;
+use crate::{JoinHandle, Scope};
+use futures_lite::prelude::*;
 use wasm_bindgen::JsCast;
 
 pub trait EventTargetExt {
     fn make_listener<E: DomEvent>(&self) -> DomEventListenerBuilder<E>;
 
-    /// create event listener stream and spawn it.
-    /// shorthand of event_target.make_listener::<E>().into_stream().for_each_spawn(f)
+    fn make_custom_event<E: CustomDomEvent>(&self, payload: E::Payload) -> CustomEventBuilder<E>;
+
+    /// listen on this container for events whose target is, or is inside, an element matching `selector`.
+    #[inline]
+    fn make_delegated_listener<E: DomEvent>(
+        &self,
+        selector: impl Into<String>,
+    ) -> DelegatedListenerBuilder<E> {
+        DelegatedListenerBuilder::new(self.make_listener::<E>(), selector.into())
+    }
+
+    /// call `f` for each event, synchronously during the dispatch.
+    /// the listener is removed when the returned binding is dropped.
     #[inline]
-    fn listen<E: DomEvent>(&self, f: impl FnMut(E::WebSysEvent) + 'static) {
-        self.make_listener::<E>().into_stream().for_each_spawn(f)
+    fn listen<E: DomEvent>(&self, f: impl FnMut(E::WebSysEvent) + 'static) -> DomEventBinding<E> {
+        self.make_listener::<E>().callback(f)
+    }
+
+    /// spawn a task calling `f` for each event. the listener is removed when `scope` is cancelled or dropped,
+    /// or when the returned handle is aborted. dropping the handle detaches the task.
+    #[inline]
+    fn listen_scoped<E: DomEvent>(
+        &self,
+        scope: &Scope,
+        f: impl FnMut(E::WebSysEvent) + 'static,
+    ) -> JoinHandle<()> {
+        scope.spawn(self.make_listener::<E>().into_stream().for_each(f))
     }
 }
 
@@ -25,39 +60,264 @@ impl<T: AsRef<web_sys::EventTarget>> EventTargetExt for T {
     fn make_listener<E: DomEvent>(&self) -> DomEventListenerBuilder<E> {
         DomEventListenerBuilder::new(self.as_ref().clone())
     }
+
+    #[inline]
+    fn make_custom_event<E: CustomDomEvent>(&self, payload: E::Payload) -> CustomEventBuilder<E> {
+        CustomEventBuilder::new(self.as_ref().clone(), payload)
+    }
 }
 
 pub trait DomEvent: Unpin + 'static {
     const TYPE_STR: &'static str;
-    type WebSysEvent: JsCast + 'static;
+    type WebSysEvent: 'static;
+
+    /// called synchronously while the event is dispatched.
+    fn cast(event: web_sys::Event) -> Option<Self::WebSysEvent>;
 }
 
 macro_rules! impl_dom_event {
-    ($t:ident, $s:expr) => {
-        pub struct $t<T = web_sys::Event>(std::marker::PhantomData<T>);
+    ($(#[$attr:meta])* $t:ident, $s:expr, $e:ty) => {
+        $(#[$attr])*
+        pub struct $t<T = $e>(std::marker::PhantomData<T>);
         impl<T: JsCast + Unpin + 'static> DomEvent for $t<T> {
             const TYPE_STR: &'static str = $s;
             type WebSysEvent = T;
+
+            #[inline]
+            fn cast(event: web_sys::Event) -> Option<T> {
+                event.dyn_into().ok()
+            }
         }
     };
 }
 
-impl_dom_event!(ClickEvent, "click");
-impl_dom_event!(KeyDownEvent, "keydown");
-impl_dom_event!(KeyUpEvent, "keyup");
-impl_dom_event!(ScrollEvent, "scroll");
-impl_dom_event!(ResizeEvent, "resize");
-impl_dom_event!(PopStateEvent, "popstate");
-impl_dom_event!(AnimationStartEvent, "animationstart");
-impl_dom_event!(AnimationEndEvent, "animationend");
-impl_dom_event!(AnimationIterationEvent, "animationiteration");
-impl_dom_event!(AnimationCancelEvent, "animationcancel");
-impl_dom_event!(ChangeEvent, "change");
-impl_dom_event!(ConnectEvent, "connect");
-impl_dom_event!(MessageEvent, "message");
-impl_dom_event!(ErrorEvent, "error");
-impl_dom_event!(MessageErrorEvent, "messageerror");
-impl_dom_event!(InstallEvent, "install");
-impl_dom_event!(ActivateEvent, "activate");
-impl_dom_event!(FetchEvent, "fetch");
-impl_dom_event!(PushEvent, "push");
+impl_dom_event!(ClickEvent, "click", web_sys::MouseEvent);
+impl_dom_event!(DblClickEvent, "dblclick", web_sys::MouseEvent);
+impl_dom_event!(ContextMenuEvent, "contextmenu", web_sys::MouseEvent);
+impl_dom_event!(MouseDownEvent, "mousedown", web_sys::MouseEvent);
+impl_dom_event!(MouseUpEvent, "mouseup", web_sys::MouseEvent);
+impl_dom_event!(MouseMoveEvent, "mousemove", web_sys::MouseEvent);
+impl_dom_event!(MouseEnterEvent, "mouseenter", web_sys::MouseEvent);
+impl_dom_event!(MouseLeaveEvent, "mouseleave", web_sys::MouseEvent);
+impl_dom_event!(MouseOverEvent, "mouseover", web_sys::MouseEvent);
+impl_dom_event!(MouseOutEvent, "mouseout", web_sys::MouseEvent);
+
+impl_dom_event!(PointerDownEvent, "pointerdown", web_sys::PointerEvent);
+impl_dom_event!(PointerMoveEvent, "pointermove", web_sys::PointerEvent);
+impl_dom_event!(PointerUpEvent, "pointerup", web_sys::PointerEvent);
+impl_dom_event!(PointerCancelEvent, "pointercancel", web_sys::PointerEvent);
+impl_dom_event!(PointerEnterEvent, "pointerenter", web_sys::PointerEvent);
+impl_dom_event!(PointerLeaveEvent, "pointerleave", web_sys::PointerEvent);
+impl_dom_event!(PointerOverEvent, "pointerover", web_sys::PointerEvent);
+impl_dom_event!(PointerOutEvent, "pointerout", web_sys::PointerEvent);
+impl_dom_event!(
+    GotPointerCaptureEvent,
+    "gotpointercapture",
+    web_sys::PointerEvent
+);
+impl_dom_event!(
+    LostPointerCaptureEvent,
+    "lostpointercapture",
+    web_sys::PointerEvent
+);
+
+impl_dom_event!(TouchStartEvent, "touchstart", web_sys::TouchEvent);
+impl_dom_event!(TouchMoveEvent, "touchmove", web_sys::TouchEvent);
+impl_dom_event!(TouchEndEvent, "touchend", web_sys::TouchEvent);
+impl_dom_event!(TouchCancelEvent, "touchcancel", web_sys::TouchEvent);
+
+impl_dom_event!(WheelEvent, "wheel", web_sys::WheelEvent);
+
+impl_dom_event!(KeyDownEvent, "keydown", web_sys::KeyboardEvent);
+impl_dom_event!(KeyUpEvent, "keyup", web_sys::KeyboardEvent);
+
+impl_dom_event!(FocusEvent, "focus", web_sys::FocusEvent);
+impl_dom_event!(BlurEvent, "blur", web_sys::FocusEvent);
+impl_dom_event!(FocusInEvent, "focusin", web_sys::FocusEvent);
+impl_dom_event!(FocusOutEvent, "focusout", web_sys::FocusEvent);
+
+impl_dom_event!(
+    /// `InputEvent` on text fields. `<select>` and checkboxes fire a plain `Event` in some browsers,
+    /// use `InputEvent<web_sys::Event>` there.
+    InputEvent,
+    "input",
+    web_sys::InputEvent
+);
+impl_dom_event!(ChangeEvent, "change", web_sys::Event);
+impl_dom_event!(SubmitEvent, "submit", web_sys::SubmitEvent);
+impl_dom_event!(ResetEvent, "reset", web_sys::Event);
+
+impl_dom_event!(DragStartEvent, "dragstart", web_sys::DragEvent);
+impl_dom_event!(DragEvent, "drag", web_sys::DragEvent);
+impl_dom_event!(DragEnterEvent, "dragenter", web_sys::DragEvent);
+impl_dom_event!(DragOverEvent, "dragover", web_sys::DragEvent);
+impl_dom_event!(DragLeaveEvent, "dragleave", web_sys::DragEvent);
+impl_dom_event!(DropEvent, "drop", web_sys::DragEvent);
+impl_dom_event!(DragEndEvent, "dragend", web_sys::DragEvent);
+
+impl_dom_event!(CopyEvent, "copy", web_sys::ClipboardEvent);
+impl_dom_event!(CutEvent, "cut", web_sys::ClipboardEvent);
+impl_dom_event!(PasteEvent, "paste", web_sys::ClipboardEvent);
+
+impl_dom_event!(ScrollEvent, "scroll", web_sys::Event);
+impl_dom_event!(ResizeEvent, "resize", web_sys::Event);
+impl_dom_event!(PopStateEvent, "popstate", web_sys::PopStateEvent);
+impl_dom_event!(HashChangeEvent, "hashchange", web_sys::HashChangeEvent);
+impl_dom_event!(LoadEvent, "load", web_sys::Event);
+impl_dom_event!(AbortEvent, "abort", web_sys::Event);
+impl_dom_event!(DomContentLoadedEvent, "DOMContentLoaded", web_sys::Event);
+impl_dom_event!(
+    BeforeUnloadEvent,
+    "beforeunload",
+    web_sys::BeforeUnloadEvent
+);
+impl_dom_event!(VisibilityChangeEvent, "visibilitychange", web_sys::Event);
+impl_dom_event!(StorageEvent, "storage", web_sys::StorageEvent);
+impl_dom_event!(OnlineEvent, "online", web_sys::Event);
+impl_dom_event!(OfflineEvent, "offline", web_sys::Event);
+
+impl_dom_event!(
+    TransitionRunEvent,
+    "transitionrun",
+    web_sys::TransitionEvent
+);
+impl_dom_event!(
+    TransitionStartEvent,
+    "transitionstart",
+    web_sys::TransitionEvent
+);
+impl_dom_event!(
+    TransitionEndEvent,
+    "transitionend",
+    web_sys::TransitionEvent
+);
+impl_dom_event!(
+    TransitionCancelEvent,
+    "transitioncancel",
+    web_sys::TransitionEvent
+);
+
+impl_dom_event!(
+    AnimationStartEvent,
+    "animationstart",
+    web_sys::AnimationEvent
+);
+impl_dom_event!(AnimationEndEvent, "animationend", web_sys::AnimationEvent);
+impl_dom_event!(
+    AnimationIterationEvent,
+    "animationiteration",
+    web_sys::AnimationEvent
+);
+impl_dom_event!(
+    AnimationCancelEvent,
+    "animationcancel",
+    web_sys::AnimationEvent
+);
+
+impl_dom_event!(ConnectEvent, "connect", web_sys::MessageEvent);
+impl_dom_event!(MessageEvent, "message", web_sys::MessageEvent);
+impl_dom_event!(
+    /// `ErrorEvent` on window and workers. use `ErrorEvent<web_sys::Event>` for elements such as `<img>`.
+    ErrorEvent,
+    "error",
+    web_sys::ErrorEvent
+);
+impl_dom_event!(MessageErrorEvent, "messageerror", web_sys::MessageEvent);
+impl_dom_event!(InstallEvent, "install", web_sys::ExtendableEvent);
+impl_dom_event!(ActivateEvent, "activate", web_sys::ExtendableEvent);
+impl_dom_event!(FetchEvent, "fetch", web_sys::FetchEvent);
+impl_dom_event!(PushEvent, "push", web_sys::PushEvent);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Node, Timeout};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    type Click = ClickEvent<web_sys::Event>;
+
+    /// dispatch a click and let spawned listener tasks run.
+    async fn click(target: &web_sys::EventTarget) {
+        target
+            .dispatch_event(&web_sys::Event::new("click").unwrap())
+            .unwrap();
+        Timeout::new(0).await;
+    }
+
+    fn counter() -> (Rc<Cell<u32>>, impl FnMut(web_sys::Event) + 'static) {
+        let count = Rc::new(Cell::new(0));
+        let f = {
+            let count = count.clone();
+            move |_| count.set(count.get() + 1)
+        };
+        (count, f)
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn listen() {
+        let target = crate::document().create_element("div").unwrap();
+
+        let (count, f) = counter();
+        let binding = target.listen::<Click>(f);
+        click(&target).await;
+        assert_eq!(count.get(), 1);
+        drop(binding);
+        click(&target).await;
+        assert_eq!(count.get(), 1);
+
+        let scope = Scope::new();
+        let (count, f) = counter();
+        let handle = target.listen_scoped::<Click>(&scope, f);
+        click(&target).await;
+        assert_eq!(count.get(), 1);
+        handle.abort();
+        click(&target).await;
+        assert_eq!(count.get(), 1);
+
+        let (count, f) = counter();
+        let handle = target.listen_scoped::<Click>(&scope, f);
+        click(&target).await;
+        scope.cancel("done");
+        click(&target).await;
+        assert_eq!(count.get(), 1);
+        assert_eq!(handle.await, None);
+
+        let scope = Scope::new();
+        let (count, f) = counter();
+        let node = Node(target.clone()).on_scoped::<Click>(&scope, f);
+        click(&node).await;
+        drop(scope);
+        click(&node).await;
+        assert_eq!(count.get(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn resize() {
+        // browsers dispatch a plain `Event`, not a `UiEvent`
+        let mut events = crate::window().make_listener::<ResizeEvent>().into_stream();
+        let event = web_sys::Event::new("resize").unwrap();
+        assert!(crate::window().dispatch_event(&event).unwrap());
+        let received = events.next().await.unwrap();
+        assert_eq!(received.type_(), "resize");
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn prevent_default() {
+        let target = crate::document().create_element("div").unwrap();
+        let _binding = target
+            .make_listener::<ClickEvent>()
+            .prevent_default()
+            .callback(|_| ());
+        // not a `MouseEvent`, still prevented
+        let init = web_sys::EventInit::new();
+        init.set_cancelable(true);
+        let event = web_sys::Event::new_with_event_init_dict("click", &init).unwrap();
+        assert!(!target.dispatch_event(&event).unwrap());
+        assert!(event.default_prevented());
+    }
+}
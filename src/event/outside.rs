@@ -0,0 +1,134 @@
+use super::{
+    BufferPolicy, ClickEvent, DEFAULT_CAPACITY, DomEvent, DomEventStream, EventTargetExt,
+    FocusInEvent,
+};
+use crate::Node;
+use futures_lite::prelude::*;
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+
+/// events on the document that happen outside a set of nodes. the listener is removed when dropped.
+pub struct OutsideEventStream<E: DomEvent> {
+    stream: DomEventStream<E>,
+    nodes: Rc<RefCell<Vec<web_sys::Node>>>,
+}
+
+impl<E: DomEvent> OutsideEventStream<E> {
+    #[inline]
+    fn new(node: web_sys::Node) -> Self {
+        let nodes = Rc::new(RefCell::new(vec![node]));
+        let stream = crate::document()
+            .make_listener::<E>()
+            .capture()
+            .bind_stream(
+                DEFAULT_CAPACITY,
+                BufferPolicy::DropNewest,
+                {
+                    let nodes = nodes.clone();
+                    move |event| is_outside(event, &nodes.borrow()).then_some(())
+                },
+                |event, ()| event,
+            );
+        Self { stream, nodes }
+    }
+
+    /// treat `node` and its descendants as inside too, e.g. the button that toggles a popover.
+    #[inline]
+    pub fn include(self, node: impl AsRef<web_sys::Node>) -> Self {
+        self.nodes.borrow_mut().push(node.as_ref().clone());
+        self
+    }
+}
+
+impl<E: DomEvent> Stream for OutsideEventStream<E> {
+    type Item = E::WebSysEvent;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next(cx)
+    }
+}
+
+#[inline]
+fn is_outside(event: &web_sys::Event, nodes: &[web_sys::Node]) -> bool {
+    // the composed path is fixed at dispatch, so targets removed by earlier handlers still count,
+    // and it reaches into shadow trees.
+    let path = event.composed_path();
+    if path.length() > 0 {
+        return !path
+            .iter()
+            .any(|target| nodes.iter().any(|node| node.is_same_node(target.dyn_ref())));
+    }
+    let target = event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Node>().ok());
+    !nodes.iter().any(|node| node.contains(target.as_ref()))
+}
+
+impl<T> Node<T>
+where
+    T: AsRef<web_sys::Node>,
+{
+    /// clicks outside this node, for dismissing dropdowns and popovers.
+    #[inline]
+    pub fn outside_clicks(&self) -> OutsideEventStream<ClickEvent> {
+        self.outside_events()
+    }
+
+    /// focus moving to an element outside this node.
+    #[inline]
+    pub fn outside_focus(&self) -> OutsideEventStream<FocusInEvent> {
+        self.outside_events()
+    }
+
+    /// e.g. `outside_events::<PointerDownEvent>()` to dismiss before the click completes.
+    #[inline]
+    pub fn outside_events<E: DomEvent>(&self) -> OutsideEventStream<E> {
+        OutsideEventStream::new(AsRef::<web_sys::Node>::as_ref(self).clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn create(parent: &web_sys::Node) -> web_sys::HtmlElement {
+        let element = crate::document()
+            .create_element("div")
+            .unwrap()
+            .unchecked_into::<web_sys::HtmlElement>();
+        parent.append_child(&element).unwrap();
+        element
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn outside_clicks() {
+        let body = crate::body();
+        let popover = create(&body);
+        let inside = create(&popover);
+        let toggle = create(&body);
+        let elsewhere = create(&body);
+        let mut clicks = Node(popover.clone()).outside_clicks().include(&toggle);
+
+        inside.click();
+        popover.click();
+        toggle.click();
+        elsewhere.click();
+        let event = clicks.next().await.unwrap();
+        assert_eq!(event.target(), Some(elsewhere.clone().into()));
+        assert!(
+            futures_lite::future::poll_once(clicks.next())
+                .await
+                .is_none()
+        );
+
+        for element in [popover, toggle, elsewhere] {
+            element.remove();
+        }
+    }
+}
@@ -1,19 +1,123 @@
-use super::{DomEvent, DomEventBinding};
+use super::{AbortEvent, DomEvent, DomEventBinding};
 use crate::chan;
 use futures_lite::prelude::*;
+use std::cell::Cell;
 use std::pin::Pin;
+use std::rc::Rc;
 use std::task::{Context, Poll};
 
-pub struct DomEventStream<E: DomEvent> {
+/// buffer size of `into_stream`.
+pub(crate) const DEFAULT_CAPACITY: usize = 16;
+
+/// what to do with a new event when the stream's buffer is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferPolicy {
+    /// drop the new event.
+    #[default]
+    DropNewest,
+    /// drop the oldest buffered event.
+    DropOldest,
+    /// buffer only the latest event, which suits `mousemove` or `scroll`. the capacity is ignored.
+    Latest,
+    /// never drop events. the capacity is ignored.
+    Unbounded,
+}
+
+impl BufferPolicy {
+    /// the capacity of the buffer. panics if `capacity` is 0 for `DropNewest` and `DropOldest`.
+    #[inline]
+    #[track_caller]
+    pub(crate) fn capacity(self, capacity: usize) -> usize {
+        match self {
+            BufferPolicy::Latest => 1,
+            BufferPolicy::Unbounded => usize::MAX,
+            BufferPolicy::DropNewest | BufferPolicy::DropOldest => {
+                assert!(
+                    capacity > 0,
+                    "event stream capacity must be at least 1, see BufferPolicy::Latest"
+                );
+                capacity
+            }
+        }
+    }
+}
+
+pub struct DomEventStream<E: DomEvent, T = <E as DomEvent>::WebSysEvent> {
     pub(crate) _binding: DomEventBinding<E>,
-    pub(crate) rx: chan::Receiver<E::WebSysEvent>,
+    pub(crate) _abort: Option<DomEventBinding<AbortEvent>>,
+    pub(crate) rx: chan::Receiver<T>,
+    pub(crate) dropped: Rc<Cell<usize>>,
+}
+
+impl<E: DomEvent, T> DomEventStream<E, T> {
+    /// number of events dropped so far because the consumer lagged behind.
+    #[inline]
+    pub fn dropped(&self) -> usize {
+        self.dropped.get()
+    }
 }
 
-impl<E: DomEvent> Stream for DomEventStream<E> {
-    type Item = E::WebSysEvent;
+impl<E: DomEvent, T> Stream for DomEventStream<E, T> {
+    type Item = T;
 
     #[inline]
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         self.rx.poll_next(cx)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{ClickEvent, EventTargetExt};
+    use wasm_bindgen_test::*;
+
+    type Click = ClickEvent<web_sys::Event>;
+
+    /// the buffered events, without waiting for more.
+    async fn drain<S: Stream + Unpin>(stream: &mut S) -> Vec<S::Item> {
+        let mut items = Vec::new();
+        while let Some(Some(item)) = futures_lite::future::poll_once(stream.next()).await {
+            items.push(item);
+        }
+        items
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn dropped() {
+        let target = crate::document().create_element("div").unwrap();
+        let stream = |policy| target.make_listener::<Click>().into_stream_with(2, policy);
+        let mut newest = stream(BufferPolicy::DropNewest);
+        let mut oldest = stream(BufferPolicy::DropOldest);
+        let mut latest = stream(BufferPolicy::Latest);
+        let mut unbounded = stream(BufferPolicy::Unbounded);
+
+        let events = (0..5)
+            .map(|_| {
+                let event = web_sys::Event::new("click").unwrap();
+                target.dispatch_event(&event).unwrap();
+                event
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(newest.dropped(), 3);
+        assert_eq!(drain(&mut newest).await, events[..2]);
+        assert_eq!(oldest.dropped(), 3);
+        assert_eq!(drain(&mut oldest).await, events[3..]);
+        assert_eq!(latest.dropped(), 4);
+        assert_eq!(drain(&mut latest).await, events[4..]);
+        assert_eq!(unbounded.dropped(), 0);
+        assert_eq!(drain(&mut unbounded).await, events);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    #[should_panic(expected = "capacity must be at least 1")]
+    fn zero_capacity() {
+        let target = crate::document().create_element("div").unwrap();
+        let _ = target
+            .make_listener::<Click>()
+            .into_stream_with(0, BufferPolicy::DropNewest);
+    }
+}
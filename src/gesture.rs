@@ -0,0 +1,699 @@
+use crate::event::{
+    DomEventBinding, EventTargetExt, PointerCancelEvent, PointerDownEvent, PointerMoveEvent,
+    PointerUpEvent,
+};
+use crate::{Node, Timeout, chan};
+use futures_lite::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// coordinates are client coordinates in CSS pixels, velocities are in pixels per millisecond.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    Tap {
+        x: f64,
+        y: f64,
+    },
+    /// emitted after the second `Tap`.
+    DoubleTap {
+        x: f64,
+        y: f64,
+    },
+    LongPress {
+        x: f64,
+        y: f64,
+    },
+    DragStart {
+        x: f64,
+        y: f64,
+    },
+    Drag {
+        x: f64,
+        y: f64,
+        /// since the previous `Drag`
+        dx: f64,
+        dy: f64,
+        vx: f64,
+        vy: f64,
+    },
+    DragEnd {
+        x: f64,
+        y: f64,
+        vx: f64,
+        vy: f64,
+    },
+    /// emitted after `DragEnd` for a fast enough drag.
+    Swipe {
+        direction: SwipeDirection,
+        velocity: f64,
+    },
+    Pinch {
+        /// distance between the two pointers relative to the start of the pinch
+        scale: f64,
+        /// clockwise rotation in degrees since the start of the pinch
+        rotation: f64,
+        center_x: f64,
+        center_y: f64,
+    },
+    PinchEnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureOptions {
+    tap_slop: f64,
+    long_press_duration: i32,
+    double_tap_interval: f64,
+    swipe_min_distance: f64,
+    swipe_min_velocity: f64,
+}
+
+impl Default for GestureOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            tap_slop: 10.0,
+            long_press_duration: 500,
+            double_tap_interval: 300.0,
+            swipe_min_distance: 30.0,
+            swipe_min_velocity: 0.3,
+        }
+    }
+}
+
+impl GestureOptions {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// max movement in pixels for a tap or long press. beyond it, a drag starts. defaults to 10.
+    #[inline]
+    pub fn tap_slop(mut self, pixels: f64) -> Self {
+        self.tap_slop = pixels;
+        self
+    }
+
+    /// defaults to 500.
+    #[inline]
+    pub fn long_press_duration(mut self, millisecs: i32) -> Self {
+        self.long_press_duration = millisecs;
+        self
+    }
+
+    /// max milliseconds between two taps of a double tap. defaults to 300.
+    #[inline]
+    pub fn double_tap_interval(mut self, millisecs: f64) -> Self {
+        self.double_tap_interval = millisecs;
+        self
+    }
+
+    /// defaults to 30 pixels and 0.3 pixels per millisecond.
+    #[inline]
+    pub fn swipe_threshold(mut self, min_distance: f64, min_velocity: f64) -> Self {
+        self.swipe_min_distance = min_distance;
+        self.swipe_min_velocity = min_velocity;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PointerInput {
+    Down,
+    Move,
+    Up,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pointer {
+    id: i32,
+    x: f64,
+    y: f64,
+}
+
+/// milliseconds after the last move in which its velocity still counts on release.
+const VELOCITY_WINDOW: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    start_x: f64,
+    start_y: f64,
+    last_time: f64,
+    vx: f64,
+    vy: f64,
+    dragging: bool,
+    long_pressed: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pinch {
+    distance: f64,
+    angle: f64,
+}
+
+/// turns pointer events into gestures, independent of the DOM.
+#[derive(Debug)]
+struct Recognizer {
+    options: GestureOptions,
+    pointers: Vec<Pointer>,
+    press: Option<Press>,
+    pinch: Option<Pinch>,
+    // set once a second pointer joins, until all pointers are released
+    multi: bool,
+    last_tap: Option<(f64, f64, f64)>,
+}
+
+impl Recognizer {
+    #[inline]
+    fn new(options: GestureOptions) -> Self {
+        Self {
+            options,
+            pointers: Vec::new(),
+            press: None,
+            pinch: None,
+            multi: false,
+            last_tap: None,
+        }
+    }
+
+    /// whether a long press timer should be running.
+    #[inline]
+    fn awaiting_long_press(&self) -> bool {
+        self.press
+            .is_some_and(|press| !press.dragging && !press.long_pressed)
+    }
+
+    #[inline]
+    fn long_press(&mut self, out: &mut VecDeque<Gesture>) {
+        if !self.awaiting_long_press() {
+            return;
+        }
+        if let (Some(press), Some(pointer)) = (&mut self.press, self.pointers.first()) {
+            press.long_pressed = true;
+            out.push_back(Gesture::LongPress {
+                x: pointer.x,
+                y: pointer.y,
+            });
+        }
+    }
+
+    #[inline]
+    fn pinch_geometry(&self) -> Option<(f64, f64, f64, f64)> {
+        let [a, b] = self.pointers.get(..2)? else {
+            return None;
+        };
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        Some((
+            dx.hypot(dy),
+            dy.atan2(dx).to_degrees(),
+            (a.x + b.x) / 2.0,
+            (a.y + b.y) / 2.0,
+        ))
+    }
+
+    #[inline]
+    fn feed(
+        &mut self,
+        input: PointerInput,
+        id: i32,
+        x: f64,
+        y: f64,
+        time: f64,
+        out: &mut VecDeque<Gesture>,
+    ) {
+        match input {
+            PointerInput::Down => self.down(id, x, y, time, out),
+            PointerInput::Move => self.moved(id, x, y, time, out),
+            PointerInput::Up => self.up(id, x, y, time, out, false),
+            PointerInput::Cancel => self.up(id, x, y, time, out, true),
+        }
+    }
+
+    #[inline]
+    fn down(&mut self, id: i32, x: f64, y: f64, time: f64, out: &mut VecDeque<Gesture>) {
+        if self.pointers.len() >= 2 || self.pointers.iter().any(|p| p.id == id) {
+            return;
+        }
+        self.pointers.push(Pointer { id, x, y });
+        if self.pointers.len() == 1 {
+            self.multi = false;
+            self.press = Some(Press {
+                start_x: x,
+                start_y: y,
+                last_time: time,
+                vx: 0.0,
+                vy: 0.0,
+                dragging: false,
+                long_pressed: false,
+            });
+            return;
+        }
+
+        // a second pointer turns the press into a pinch
+        self.multi = true;
+        if let Some(press) = self.press.take()
+            && press.dragging
+        {
+            let first = self.pointers[0];
+            out.push_back(Gesture::DragEnd {
+                x: first.x,
+                y: first.y,
+                vx: 0.0,
+                vy: 0.0,
+            });
+        }
+        if let Some((distance, angle, ..)) = self.pinch_geometry() {
+            self.pinch = Some(Pinch { distance, angle });
+        }
+    }
+
+    #[inline]
+    fn moved(&mut self, id: i32, x: f64, y: f64, time: f64, out: &mut VecDeque<Gesture>) {
+        let Some(pointer) = self.pointers.iter_mut().find(|p| p.id == id) else {
+            return;
+        };
+        let (last_x, last_y) = (pointer.x, pointer.y);
+        pointer.x = x;
+        pointer.y = y;
+
+        if let Some(pinch) = self.pinch {
+            if let Some((distance, angle, center_x, center_y)) = self.pinch_geometry() {
+                let scale = match pinch.distance > 0.0 {
+                    true => distance / pinch.distance,
+                    false => 1.0,
+                };
+                let rotation = (angle - pinch.angle + 540.0) % 360.0 - 180.0;
+                out.push_back(Gesture::Pinch {
+                    scale,
+                    rotation,
+                    center_x,
+                    center_y,
+                });
+            }
+            return;
+        }
+
+        let options = self.options;
+        let Some(press) = &mut self.press else {
+            return;
+        };
+        if !press.dragging {
+            let distance = (x - press.start_x).hypot(y - press.start_y);
+            if distance <= options.tap_slop || press.long_pressed {
+                return;
+            }
+            press.dragging = true;
+            out.push_back(Gesture::DragStart {
+                x: press.start_x,
+                y: press.start_y,
+            });
+        }
+
+        let (dx, dy) = (x - last_x, y - last_y);
+        let dt = time - press.last_time;
+        if dt > VELOCITY_WINDOW {
+            press.vx = dx / dt;
+            press.vy = dy / dt;
+        } else if dt > 0.0 {
+            // smooth out jitter of single samples
+            press.vx = 0.8 * dx / dt + 0.2 * press.vx;
+            press.vy = 0.8 * dy / dt + 0.2 * press.vy;
+        }
+        press.last_time = time;
+        out.push_back(Gesture::Drag {
+            x,
+            y,
+            dx,
+            dy,
+            vx: press.vx,
+            vy: press.vy,
+        });
+    }
+
+    #[inline]
+    fn up(
+        &mut self,
+        id: i32,
+        x: f64,
+        y: f64,
+        time: f64,
+        out: &mut VecDeque<Gesture>,
+        cancel: bool,
+    ) {
+        let Some(index) = self.pointers.iter().position(|p| p.id == id) else {
+            return;
+        };
+        self.pointers.remove(index);
+
+        if self.pinch.is_some() {
+            self.pinch = None;
+            out.push_back(Gesture::PinchEnd);
+            return;
+        }
+        if self.multi {
+            return;
+        }
+
+        let Some(press) = self.press.take() else {
+            return;
+        };
+        let options = self.options;
+        if press.dragging {
+            // a pointer held still before release has no velocity left
+            let stale = time - press.last_time > VELOCITY_WINDOW;
+            let (vx, vy) = match cancel || stale {
+                true => (0.0, 0.0),
+                false => (press.vx, press.vy),
+            };
+            out.push_back(Gesture::DragEnd { x, y, vx, vy });
+
+            let (dx, dy) = (x - press.start_x, y - press.start_y);
+            let velocity = vx.hypot(vy);
+            if dx.hypot(dy) >= options.swipe_min_distance && velocity >= options.swipe_min_velocity
+            {
+                let direction = match (dx.abs() >= dy.abs(), dx >= 0.0, dy >= 0.0) {
+                    (true, true, _) => SwipeDirection::Right,
+                    (true, false, _) => SwipeDirection::Left,
+                    (false, _, true) => SwipeDirection::Down,
+                    (false, _, false) => SwipeDirection::Up,
+                };
+                out.push_back(Gesture::Swipe {
+                    direction,
+                    velocity,
+                });
+            }
+            return;
+        }
+        if cancel || press.long_pressed {
+            return;
+        }
+
+        out.push_back(Gesture::Tap { x, y });
+        match self.last_tap.take() {
+            Some((last_time, last_x, last_y))
+                if time - last_time <= options.double_tap_interval
+                    && (x - last_x).hypot(y - last_y) <= options.tap_slop * 2.0 =>
+            {
+                out.push_back(Gesture::DoubleTap { x, y });
+            }
+            _ => self.last_tap = Some((time, x, y)),
+        }
+    }
+}
+
+type Input = (PointerInput, web_sys::PointerEvent);
+
+/// yields recognized gestures. listeners are removed and pointer captures released when dropped.
+///
+/// set `touch-action: none` on the element so that touch input is not consumed by scrolling or zooming.
+pub struct GestureStream {
+    element: web_sys::Element,
+    _bindings: (
+        DomEventBinding<PointerDownEvent>,
+        DomEventBinding<PointerMoveEvent>,
+        DomEventBinding<PointerUpEvent>,
+        DomEventBinding<PointerCancelEvent>,
+    ),
+    captured: Rc<RefCell<Vec<i32>>>,
+    rx: chan::Receiver<Input>,
+    recognizer: Recognizer,
+    long_press_timer: Option<Timeout>,
+    gestures: VecDeque<Gesture>,
+}
+
+impl Drop for GestureStream {
+    #[inline]
+    fn drop(&mut self) {
+        for id in self.captured.borrow_mut().drain(..) {
+            let _ = self.element.release_pointer_capture(id);
+        }
+    }
+}
+
+impl Stream for GestureStream {
+    type Item = Gesture;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(gesture) = this.gestures.pop_front() {
+                return Poll::Ready(Some(gesture));
+            }
+
+            if let Some(timer) = &mut this.long_press_timer
+                && timer.poll(cx).is_ready()
+            {
+                this.long_press_timer.take();
+                this.recognizer.long_press(&mut this.gestures);
+                continue;
+            }
+
+            match this.rx.poll_next(cx) {
+                Poll::Ready(Some((input, event))) => {
+                    this.recognizer.feed(
+                        input,
+                        event.pointer_id(),
+                        event.client_x() as f64,
+                        event.client_y() as f64,
+                        event.time_stamp(),
+                        &mut this.gestures,
+                    );
+                    match this.recognizer.awaiting_long_press() {
+                        true if input == PointerInput::Down => {
+                            let duration = this.recognizer.options.long_press_duration;
+                            this.long_press_timer.replace(Timeout::new(duration));
+                        }
+                        true => {}
+                        false => {
+                            this.long_press_timer.take();
+                        }
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<T> Node<T>
+where
+    T: AsRef<web_sys::Element>,
+{
+    /// recognize gestures from pointer events on this element. pointers are captured while pressed.
+    #[inline]
+    pub fn gestures(&self, options: GestureOptions) -> GestureStream {
+        let element = AsRef::<web_sys::Element>::as_ref(self).clone();
+        let captured = Rc::new(RefCell::new(Vec::new()));
+        let (tx, rx) = chan::<Input>(usize::MAX);
+
+        let send = {
+            let tx = tx.clone();
+            move |input: Input| {
+                if let Err(err) = tx.send(input) {
+                    crate::error!("fail to send: {}", err.to_string());
+                }
+            }
+        };
+        let down = element.make_listener::<PointerDownEvent>().callback({
+            let element = element.clone();
+            let captured = captured.clone();
+            let send = send.clone();
+            move |event| {
+                let id = event.pointer_id();
+                if element.set_pointer_capture(id).is_ok() {
+                    captured.borrow_mut().push(id);
+                }
+                send((PointerInput::Down, event));
+            }
+        });
+        let moved = element.make_listener::<PointerMoveEvent>().callback({
+            let send = send.clone();
+            move |event| send((PointerInput::Move, event))
+        });
+        // the browser releases the capture on pointerup and pointercancel
+        let up = element.make_listener::<PointerUpEvent>().callback({
+            let captured = captured.clone();
+            let send = send.clone();
+            move |event| {
+                let id = event.pointer_id();
+                captured.borrow_mut().retain(|&captured| captured != id);
+                send((PointerInput::Up, event));
+            }
+        });
+        let cancel = element.make_listener::<PointerCancelEvent>().callback({
+            let captured = captured.clone();
+            move |event| {
+                let id = event.pointer_id();
+                captured.borrow_mut().retain(|&captured| captured != id);
+                send((PointerInput::Cancel, event));
+            }
+        });
+
+        GestureStream {
+            element,
+            _bindings: (down, moved, up, cancel),
+            captured,
+            rx,
+            recognizer: Recognizer::new(options),
+            long_press_timer: None,
+            gestures: VecDeque::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    struct Harness {
+        recognizer: Recognizer,
+        out: VecDeque<Gesture>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            Self {
+                recognizer: Recognizer::new(GestureOptions::new()),
+                out: VecDeque::new(),
+            }
+        }
+
+        fn feed(
+            &mut self,
+            input: PointerInput,
+            id: i32,
+            x: f64,
+            y: f64,
+            time: f64,
+        ) -> Vec<Gesture> {
+            self.recognizer.feed(input, id, x, y, time, &mut self.out);
+            self.out.drain(..).collect()
+        }
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn tap() {
+        let mut h = Harness::new();
+        assert_eq!(h.feed(PointerInput::Down, 1, 0.0, 0.0, 0.0), []);
+        assert!(h.recognizer.awaiting_long_press());
+        assert_eq!(h.feed(PointerInput::Move, 1, 3.0, 0.0, 50.0), []);
+        assert_eq!(
+            h.feed(PointerInput::Up, 1, 3.0, 0.0, 100.0),
+            [Gesture::Tap { x: 3.0, y: 0.0 }]
+        );
+        assert!(!h.recognizer.awaiting_long_press());
+
+        h.feed(PointerInput::Down, 1, 3.0, 0.0, 200.0);
+        assert_eq!(
+            h.feed(PointerInput::Up, 1, 3.0, 0.0, 250.0),
+            [
+                Gesture::Tap { x: 3.0, y: 0.0 },
+                Gesture::DoubleTap { x: 3.0, y: 0.0 }
+            ]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn long_press() {
+        let mut h = Harness::new();
+        h.feed(PointerInput::Down, 1, 5.0, 5.0, 0.0);
+        h.recognizer.long_press(&mut h.out);
+        assert_eq!(
+            h.out.drain(..).collect::<Vec<_>>(),
+            [Gesture::LongPress { x: 5.0, y: 5.0 }]
+        );
+        assert!(!h.recognizer.awaiting_long_press());
+        assert_eq!(h.feed(PointerInput::Up, 1, 5.0, 5.0, 600.0), []);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn drag_and_swipe() {
+        let mut h = Harness::new();
+        h.feed(PointerInput::Down, 1, 0.0, 0.0, 0.0);
+        let gestures = h.feed(PointerInput::Move, 1, 20.0, 0.0, 10.0);
+        assert_eq!(gestures[0], Gesture::DragStart { x: 0.0, y: 0.0 });
+        assert!(matches!(gestures[1], Gesture::Drag { dx: 20.0, .. }));
+        assert!(!h.recognizer.awaiting_long_press());
+        h.feed(PointerInput::Move, 1, 60.0, 5.0, 20.0);
+
+        let gestures = h.feed(PointerInput::Up, 1, 60.0, 5.0, 30.0);
+        assert!(matches!(gestures[0], Gesture::DragEnd { x: 60.0, .. }));
+        assert!(matches!(
+            gestures[1],
+            Gesture::Swipe {
+                direction: SwipeDirection::Right,
+                ..
+            }
+        ));
+
+        // slow drags are not swipes
+        h.feed(PointerInput::Down, 1, 0.0, 0.0, 1000.0);
+        h.feed(PointerInput::Move, 1, 0.0, 40.0, 2000.0);
+        let gestures = h.feed(PointerInput::Up, 1, 0.0, 40.0, 3000.0);
+        assert_eq!(gestures.len(), 1);
+
+        // fast drags paused before release are not swipes
+        h.feed(PointerInput::Down, 1, 0.0, 0.0, 4000.0);
+        h.feed(PointerInput::Move, 1, 30.0, 0.0, 4010.0);
+        h.feed(PointerInput::Move, 1, 60.0, 0.0, 4020.0);
+        let gestures = h.feed(PointerInput::Up, 1, 60.0, 0.0, 5020.0);
+        assert_eq!(
+            gestures,
+            [Gesture::DragEnd {
+                x: 60.0,
+                y: 0.0,
+                vx: 0.0,
+                vy: 0.0
+            }]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn pinch() {
+        let mut h = Harness::new();
+        h.feed(PointerInput::Down, 1, 0.0, 0.0, 0.0);
+        assert_eq!(h.feed(PointerInput::Down, 2, 10.0, 0.0, 10.0), []);
+        assert!(!h.recognizer.awaiting_long_press());
+
+        let gestures = h.feed(PointerInput::Move, 2, 0.0, 20.0, 20.0);
+        let [
+            Gesture::Pinch {
+                scale,
+                rotation,
+                center_x,
+                center_y,
+            },
+        ] = gestures[..]
+        else {
+            panic!("{gestures:?}");
+        };
+        assert!((scale - 2.0).abs() < 1e-9);
+        assert!((rotation - 90.0).abs() < 1e-9);
+        assert_eq!((center_x, center_y), (0.0, 10.0));
+
+        assert_eq!(
+            h.feed(PointerInput::Up, 2, 0.0, 20.0, 30.0),
+            [Gesture::PinchEnd]
+        );
+        // releasing the remaining pointer is not a tap
+        assert_eq!(h.feed(PointerInput::Up, 1, 0.0, 0.0, 40.0), []);
+    }
+}
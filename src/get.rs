@@ -1,54 +1,199 @@
 use super::Node;
-use crate::Storage;
+use crate::event::{DomContentLoadedEvent, EventTargetExt};
+use crate::{Cookies, DomError, Storage};
+use futures_lite::prelude::*;
+use std::cell::OnceCell;
 use wasm_bindgen::prelude::*;
 
 thread_local! {
-    static WINDOW: web_sys::Window = web_sys::window().unwrap_throw();
-    static DOCUMENT: web_sys::Document = window().document().unwrap_throw();
-    static HISTORY: web_sys::History = window().history().unwrap_throw();
-    static LOCAL_STORAGE: Storage = Storage(window().local_storage().unwrap_throw().unwrap_throw());
-    static SESSION_STORAGE: Storage = Storage(window().session_storage().unwrap_throw().unwrap_throw());
-    static DOCUMENT_ELEMENT: Node<web_sys::Element> = Node(document().document_element().unwrap_throw());
-    static HEAD: Node<web_sys::HtmlHeadElement> = Node(document().head().unwrap_throw().unchecked_into());
-    static BODY: Node<web_sys::HtmlElement> = Node(document().body().unwrap_throw().unchecked_into());
+    static WINDOW: Option<web_sys::Window> = web_sys::window();
+    static DOCUMENT: Option<web_sys::Document> = try_window().and_then(|window| window.document());
+    static HISTORY: Option<web_sys::History> = try_window().and_then(|window| window.history().ok());
+    static LOCATION: Option<web_sys::Location> = try_window().map(|window| window.location());
+    static LOCAL_STORAGE: Result<Storage, DomError> = get_storage(|window| window.local_storage(), "localStorage");
+    static SESSION_STORAGE: Result<Storage, DomError> = get_storage(|window| window.session_storage(), "sessionStorage");
+    static DOCUMENT_ELEMENT: OnceCell<Node<web_sys::Element>> = const { OnceCell::new() };
+    static HEAD: OnceCell<Node<web_sys::HtmlHeadElement>> = const { OnceCell::new() };
+    static BODY: OnceCell<Node<web_sys::HtmlElement>> = const { OnceCell::new() };
 }
 
+/// `None` inside Web Workers.
 #[inline]
-pub fn window() -> web_sys::Window {
+pub fn try_window() -> Option<web_sys::Window> {
     WINDOW.with(Clone::clone)
 }
 
 #[inline]
-pub fn document() -> web_sys::Document {
+#[track_caller]
+pub fn window() -> web_sys::Window {
+    try_window().unwrap_throw()
+}
+
+#[inline]
+pub fn try_document() -> Option<web_sys::Document> {
     DOCUMENT.with(Clone::clone)
 }
 
 #[inline]
-pub fn history() -> web_sys::History {
+#[track_caller]
+pub fn document() -> web_sys::Document {
+    try_document().unwrap_throw()
+}
+
+#[inline]
+pub fn try_history() -> Option<web_sys::History> {
     HISTORY.with(Clone::clone)
 }
 
 #[inline]
+#[track_caller]
+pub fn history() -> web_sys::History {
+    try_history().unwrap_throw()
+}
+
+#[inline]
+pub fn try_location() -> Option<web_sys::Location> {
+    LOCATION.with(Clone::clone)
+}
+
+#[inline]
+#[track_caller]
+pub fn location() -> web_sys::Location {
+    try_location().unwrap_throw()
+}
+
+#[inline]
+fn get_storage(
+    f: impl FnOnce(&web_sys::Window) -> Result<Option<web_sys::Storage>, JsValue>,
+    name: &str,
+) -> Result<Storage, DomError> {
+    let not_available = || DomError::new("NotSupportedError", format!("{name} is not available"));
+    let window = try_window().ok_or_else(not_available)?;
+    match f(&window)? {
+        Some(storage) => Ok(Storage(storage)),
+        None => Err(not_available()),
+    }
+}
+
+#[inline]
+#[track_caller]
 pub fn local_storage() -> Storage {
+    try_local_storage().unwrap_throw()
+}
+
+/// fails with "SecurityError" e.g. in sandboxed iframes or when storage access is denied.
+#[inline]
+pub fn try_local_storage() -> Result<Storage, DomError> {
     LOCAL_STORAGE.with(Clone::clone)
 }
 
 #[inline]
+#[track_caller]
 pub fn session_storage() -> Storage {
+    try_session_storage().unwrap_throw()
+}
+
+#[inline]
+pub fn try_session_storage() -> Result<Storage, DomError> {
     SESSION_STORAGE.with(Clone::clone)
 }
 
 #[inline]
+#[track_caller]
+pub fn cookies() -> Cookies {
+    try_cookies().unwrap_throw()
+}
+
+/// fails with "NotSupportedError" without an HTML document, e.g. inside Web Workers.
+#[inline]
+pub fn try_cookies() -> Result<Cookies, DomError> {
+    try_document()
+        .and_then(|document| document.dyn_into::<web_sys::HtmlDocument>().ok())
+        .map(Cookies)
+        .ok_or_else(|| DomError::new("NotSupportedError", "document.cookie is not available"))
+}
+
+// unlike window or document, these may appear later (e.g. scripts in <head> run before <body> exists),
+// so `None` is not cached.
+#[inline]
+fn get_or_lookup<T: Clone>(
+    cell: &'static std::thread::LocalKey<OnceCell<T>>,
+    lookup: impl FnOnce(web_sys::Document) -> Option<T>,
+) -> Option<T> {
+    cell.with(|cell| match cell.get() {
+        Some(value) => Some(value.clone()),
+        None => {
+            let value = lookup(try_document()?)?;
+            Some(cell.get_or_init(|| value).clone())
+        }
+    })
+}
+
+#[inline]
+pub fn try_document_element() -> Option<Node<web_sys::Element>> {
+    get_or_lookup(&DOCUMENT_ELEMENT, |document| {
+        document.document_element().map(Node)
+    })
+}
+
+#[inline]
+#[track_caller]
 pub fn document_element() -> Node<web_sys::Element> {
-    DOCUMENT_ELEMENT.with(Clone::clone)
+    try_document_element().unwrap_throw()
+}
+
+#[inline]
+pub fn try_head() -> Option<Node<web_sys::HtmlHeadElement>> {
+    get_or_lookup(&HEAD, |document| document.head().map(Node))
 }
 
 #[inline]
+#[track_caller]
 pub fn head() -> Node<web_sys::HtmlHeadElement> {
-    HEAD.with(Clone::clone)
+    try_head().unwrap_throw()
+}
+
+#[inline]
+pub fn try_body() -> Option<Node<web_sys::HtmlElement>> {
+    get_or_lookup(&BODY, |document| document.body().map(Node))
 }
 
 #[inline]
+#[track_caller]
 pub fn body() -> Node<web_sys::HtmlElement> {
-    BODY.with(Clone::clone)
+    try_body().unwrap_throw()
+}
+
+/// whether `DOMContentLoaded` has fired. module and deferred scripts run before it,
+/// while `readyState` is already "interactive".
+#[inline]
+fn dom_content_loaded(document: &web_sys::Document) -> bool {
+    match document.ready_state().as_str() {
+        "loading" => false,
+        "complete" => true,
+        _ => window()
+            .performance()
+            .and_then(|performance| {
+                performance
+                    .get_entries_by_type("navigation")
+                    .get(0)
+                    .dyn_into::<web_sys::PerformanceNavigationTiming>()
+                    .ok()
+            })
+            // without navigation timing, treat "interactive" as loaded
+            .is_none_or(|timing| timing.dom_content_loaded_event_start() > 0.0),
+    }
+}
+
+/// resolves once `DOMContentLoaded` has fired. resolves immediately if it already has.
+#[inline]
+pub async fn dom_ready() {
+    let document = document();
+    if dom_content_loaded(&document) {
+        return;
+    }
+    let mut loaded = document
+        .make_listener::<DomContentLoadedEvent>()
+        .into_stream();
+    loaded.next().await;
 }
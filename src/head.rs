@@ -0,0 +1,315 @@
+use crate::{DomError, Scope, State};
+use futures_lite::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// attribute marking `<link>` tags created by `set_link`.
+const LINK_KEY: &str = "data-head-key";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Title,
+    /// `<meta name=..>` or `<meta property=..>`
+    Meta {
+        attr: &'static str,
+        value: String,
+    },
+    Link(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Text(String),
+    Link { rel: String, href: String },
+}
+
+/// values set for one key, latest last, and the value to restore once all are gone.
+#[derive(Debug)]
+struct Stack<V> {
+    base: Option<V>,
+    entries: Vec<(usize, V)>,
+}
+
+impl<V> Stack<V> {
+    #[inline]
+    fn new(base: Option<V>) -> Self {
+        Self {
+            base,
+            entries: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn current(&self) -> Option<&V> {
+        match self.entries.last() {
+            Some((_, value)) => Some(value),
+            None => self.base.as_ref(),
+        }
+    }
+
+    /// whether `id` is the visible entry.
+    #[inline]
+    fn update(&mut self, id: usize, value: V) -> bool {
+        let Some(index) = self.entries.iter().position(|(i, _)| *i == id) else {
+            return false;
+        };
+        self.entries[index].1 = value;
+        index + 1 == self.entries.len()
+    }
+
+    /// whether the visible value changed.
+    #[inline]
+    fn remove(&mut self, id: usize) -> bool {
+        let Some(index) = self.entries.iter().position(|(i, _)| *i == id) else {
+            return false;
+        };
+        self.entries.remove(index);
+        index == self.entries.len()
+    }
+}
+
+thread_local! {
+    static STACKS: RefCell<HashMap<Key, Stack<Value>>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+#[inline]
+fn find(tag: &str, attr: &str, value: &str) -> Option<web_sys::Element> {
+    // compare attributes instead of building a selector, which would need escaping
+    let elements = crate::try_head()?.get_elements_by_tag_name(tag);
+    (0..elements.length())
+        .filter_map(|i| elements.item(i))
+        .find(|element| element.get_attribute(attr).as_deref() == Some(value))
+}
+
+#[inline]
+fn find_key(key: &Key) -> Option<web_sys::Element> {
+    match key {
+        Key::Title => None,
+        Key::Meta { attr, value } => find("meta", attr, value),
+        // adopt a server-rendered `<link rel=canonical>` for key "canonical"
+        Key::Link(key) => find("link", LINK_KEY, key).or_else(|| find("link", "rel", key)),
+    }
+}
+
+#[inline]
+fn read(key: &Key) -> Option<Value> {
+    if *key == Key::Title {
+        return Some(Value::Text(crate::try_document()?.title()));
+    }
+    let element = find_key(key)?;
+    let attr = |name| element.get_attribute(name).unwrap_or_default();
+    Some(match key {
+        Key::Title => unreachable!(),
+        Key::Meta { .. } => Value::Text(attr("content")),
+        Key::Link(_) => Value::Link {
+            rel: attr("rel"),
+            href: attr("href"),
+        },
+    })
+}
+
+/// apply `value`, creating the tag if needed, or remove the tag for `None`.
+#[inline]
+fn write(key: &Key, value: Option<&Value>) -> Result<(), DomError> {
+    if *key == Key::Title {
+        if let Some(Value::Text(title)) = value {
+            crate::document().set_title(title);
+        }
+        return Ok(());
+    }
+    let Some(value) = value else {
+        if let Some(element) = find_key(key) {
+            element.remove();
+        }
+        return Ok(());
+    };
+    let element = match find_key(key) {
+        Some(element) => element,
+        None => {
+            let (tag, attr, attr_value) = match key {
+                Key::Title => unreachable!(),
+                Key::Meta { attr, value } => ("meta", *attr, value),
+                Key::Link(key) => ("link", LINK_KEY, key),
+            };
+            let element = crate::document().create_element(tag)?;
+            element.set_attribute(attr, attr_value)?;
+            crate::try_head()
+                .ok_or_else(|| DomError::new("NotFoundError", "document has no <head>"))?
+                .append_child(&element)?;
+            element
+        }
+    };
+    match value {
+        Value::Text(content) => element.set_attribute("content", content)?,
+        Value::Link { rel, href } => {
+            element.set_attribute("rel", rel)?;
+            element.set_attribute("href", href)?;
+        }
+    }
+    Ok(())
+}
+
+/// set `key` to `value` until `scope` ends. returns the entry id.
+#[inline]
+fn push(scope: &Scope, key: Key, value: Value) -> Result<usize, DomError> {
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    STACKS.with(|stacks| {
+        stacks
+            .borrow_mut()
+            .entry(key.clone())
+            .or_insert_with(|| Stack::new(read(&key)))
+            .entries
+            .push((id, value.clone()));
+    });
+    let written = write(&key, Some(&value));
+    scope.on_cleanup(move || pop(key, id));
+    written.map(|_| id)
+}
+
+#[inline]
+fn update(key: &Key, id: usize, value: Value) {
+    let visible = STACKS.with(|stacks| {
+        stacks
+            .borrow_mut()
+            .get_mut(key)
+            .is_some_and(|stack| stack.update(id, value.clone()))
+    });
+    if visible && let Err(err) = write(key, Some(&value)) {
+        crate::error!("{err}");
+    }
+}
+
+#[inline]
+fn pop(key: Key, id: usize) {
+    let restore = STACKS.with(|stacks| {
+        let mut stacks = stacks.borrow_mut();
+        let stack = stacks.get_mut(&key)?;
+        let changed = stack.remove(id);
+        let restore = changed.then(|| stack.current().cloned());
+        if stack.entries.is_empty() {
+            stacks.remove(&key);
+        }
+        restore
+    });
+    if let Some(value) = restore
+        && let Err(err) = write(&key, value.as_ref())
+    {
+        crate::error!("{err}");
+    }
+}
+
+/// set `document.title` until `scope` ends.
+/// when several living scopes set the same tag, the latest one wins, e.g. a route's title over the app's.
+#[inline]
+pub fn set_title(scope: &Scope, title: impl Into<String>) {
+    // writing the title does not fail
+    let _ = push(scope, Key::Title, Value::Text(title.into()));
+}
+
+/// keep `document.title` in sync with `state` until `scope` ends.
+#[inline]
+pub fn bind_title<T: Copy + Eq + 'static>(
+    scope: &Scope,
+    state: &State<T>,
+    title: impl Fn(T) -> String + 'static,
+) {
+    let Ok(id) = push(scope, Key::Title, Value::Text(title(state.get()))) else {
+        return;
+    };
+    let mut changes = state.watch();
+    scope.spawn(async move {
+        while let Some(change) = changes.next().await {
+            update(&Key::Title, id, Value::Text(title(change.value)));
+        }
+    });
+}
+
+/// upsert `<meta name={name} content={content}>` until `scope` ends, e.g. "description".
+#[inline]
+#[track_caller]
+pub fn set_meta_name(scope: &Scope, name: &str, content: impl Into<String>) {
+    try_set_meta_name(scope, name, content).unwrap_throw()
+}
+
+#[inline]
+pub fn try_set_meta_name(
+    scope: &Scope,
+    name: &str,
+    content: impl Into<String>,
+) -> Result<(), DomError> {
+    let key = Key::Meta {
+        attr: "name",
+        value: name.into(),
+    };
+    push(scope, key, Value::Text(content.into())).map(|_| ())
+}
+
+/// upsert `<meta property={property} content={content}>` until `scope` ends, e.g. "og:title".
+#[inline]
+#[track_caller]
+pub fn set_meta_property(scope: &Scope, property: &str, content: impl Into<String>) {
+    try_set_meta_property(scope, property, content).unwrap_throw()
+}
+
+#[inline]
+pub fn try_set_meta_property(
+    scope: &Scope,
+    property: &str,
+    content: impl Into<String>,
+) -> Result<(), DomError> {
+    let key = Key::Meta {
+        attr: "property",
+        value: property.into(),
+    };
+    push(scope, key, Value::Text(content.into())).map(|_| ())
+}
+
+/// upsert `<link rel={rel} href={href}>` identified by `key` until `scope` ends.
+/// a key equal to a rel, e.g. "canonical" or "icon", also adopts an existing tag with that rel.
+/// use distinct keys for several tags with the same rel, e.g. "preload-font".
+#[inline]
+#[track_caller]
+pub fn set_link(scope: &Scope, key: &str, rel: &str, href: &str) {
+    try_set_link(scope, key, rel, href).unwrap_throw()
+}
+
+#[inline]
+pub fn try_set_link(scope: &Scope, key: &str, rel: &str, href: &str) -> Result<(), DomError> {
+    let value = Value::Link {
+        rel: rel.into(),
+        href: href.into(),
+    };
+    push(scope, Key::Link(key.into()), value).map(|_| ())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn stack() {
+        let mut stack = Stack::new(Some("base"));
+        assert_eq!(stack.current(), Some(&"base"));
+        stack.entries.push((0, "app"));
+        stack.entries.push((1, "page"));
+        assert_eq!(stack.current(), Some(&"page"));
+
+        // hidden entries update silently
+        assert!(!stack.update(0, "app 2"));
+        assert!(stack.update(1, "page 2"));
+
+        // the app scope ending first keeps the page value
+        assert!(!stack.remove(0));
+        assert_eq!(stack.current(), Some(&"page 2"));
+        assert!(stack.remove(1));
+        assert_eq!(stack.current(), Some(&"base"));
+        assert!(!stack.remove(1));
+
+        let stack = Stack::<&str>::new(None);
+        assert_eq!(stack.current(), None);
+    }
+}
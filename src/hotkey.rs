@@ -0,0 +1,514 @@
+use crate::event::{DomEventBinding, EventTargetExt, KeyDownEvent};
+use crate::{chan, try_window};
+use futures_lite::prelude::*;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+/// a key with modifiers, e.g. "Ctrl+S", "Shift+?", "Mod+Enter".
+/// "Mod" is "Meta" (Cmd) on Apple platforms and "Ctrl" elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    pub modifiers: Modifiers,
+    /// lowercase `KeyboardEvent.key`, e.g. "s", "?", "enter", "arrowup", " "
+    pub key: String,
+    /// for pressed keys, the letter or digit of `KeyboardEvent.code` when Alt or Shift changed `key`,
+    /// e.g. "s" for "ß" (Option+S on macOS) or "1" for "!" (Shift+1).
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    UnknownModifier(String),
+    MissingKey(String),
+}
+
+impl std::fmt::Display for ParseError {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty hotkey"),
+            ParseError::UnknownModifier(modifier) => write!(f, "unknown modifier: {modifier}"),
+            ParseError::MissingKey(combo) => write!(f, "missing key: {combo}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[inline]
+fn is_apple_platform() -> bool {
+    thread_local! {
+        static IS_APPLE: bool = try_window()
+            .and_then(|window| window.navigator().platform().ok())
+            .is_some_and(|platform| {
+                ["Mac", "iPhone", "iPad", "iPod"]
+                    .iter()
+                    .any(|name| platform.contains(name))
+            });
+    }
+    IS_APPLE.with(|is_apple| *is_apple)
+}
+
+#[inline]
+fn normalize_key(key: &str) -> String {
+    let key = key.to_lowercase();
+    match key.as_str() {
+        "esc" => "escape".into(),
+        "space" | "spacebar" => " ".into(),
+        "return" => "enter".into(),
+        "del" => "delete".into(),
+        "ins" => "insert".into(),
+        "up" => "arrowup".into(),
+        "down" => "arrowdown".into(),
+        "left" => "arrowleft".into(),
+        "right" => "arrowright".into(),
+        "plus" => "+".into(),
+        _ => key,
+    }
+}
+
+impl KeyCombo {
+    #[inline]
+    fn parse(s: &str, apple: bool) -> Result<Self, ParseError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        // "+" alone or as the last part, e.g. "Ctrl++"
+        let (modifier_part, key) = match s.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None if s == "+" => ("", "+"),
+            None => match s.rsplit_once('+') {
+                Some((modifiers, key)) => (modifiers, key),
+                None => ("", s),
+            },
+        };
+        if key.is_empty() {
+            return Err(ParseError::MissingKey(s.into()));
+        }
+
+        let mut modifiers = Modifiers::default();
+        for modifier in modifier_part.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" | "option" | "opt" => modifiers.alt = true,
+                "shift" => modifiers.shift = true,
+                "meta" | "cmd" | "command" | "super" | "win" => modifiers.meta = true,
+                "mod" if apple => modifiers.meta = true,
+                "mod" => modifiers.ctrl = true,
+                _ => return Err(ParseError::UnknownModifier(modifier.into())),
+            }
+        }
+
+        Ok(Self {
+            modifiers,
+            key: normalize_key(key),
+            code: None,
+        })
+    }
+
+    #[inline]
+    fn pressed(modifiers: Modifiers, key: &str, code: &str) -> Self {
+        let key = normalize_key(key);
+        let code = code
+            .strip_prefix("Key")
+            .or_else(|| code.strip_prefix("Digit"))
+            .map(str::to_lowercase)
+            .filter(|code| (modifiers.alt || modifiers.shift) && *code != key);
+        Self {
+            modifiers,
+            key,
+            code,
+        }
+    }
+
+    #[inline]
+    pub fn from_event(event: &web_sys::KeyboardEvent) -> Self {
+        let modifiers = Modifiers {
+            ctrl: event.ctrl_key(),
+            alt: event.alt_key(),
+            shift: event.shift_key(),
+            meta: event.meta_key(),
+        };
+        Self::pressed(modifiers, &event.key(), &event.code())
+    }
+
+    /// shift is implied by symbols such as "?", so it is only compared for letters and named keys,
+    /// and when falling back to `code`.
+    #[inline]
+    pub fn matches(&self, pressed: &KeyCombo) -> bool {
+        let mut chars = self.key.chars();
+        let is_symbol =
+            matches!((chars.next(), chars.next()), (Some(c), None) if !c.is_alphabetic());
+        let shift = self.modifiers.shift == pressed.modifiers.shift;
+        let by_key = self.key == pressed.key && ((is_symbol && !self.modifiers.shift) || shift);
+        let by_code = pressed.code.as_ref() == Some(&self.key) && shift;
+        self.modifiers.ctrl == pressed.modifiers.ctrl
+            && self.modifiers.alt == pressed.modifiers.alt
+            && self.modifiers.meta == pressed.modifiers.meta
+            && (by_key || by_code)
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, is_apple_platform())
+    }
+}
+
+/// one or more `KeyCombo`s separated by spaces, e.g. "g i" or "Ctrl+K Ctrl+C".
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hotkey(pub Vec<KeyCombo>);
+
+impl Hotkey {
+    #[inline]
+    fn parse(s: &str, apple: bool) -> Result<Self, ParseError> {
+        let combos = s
+            .split_whitespace()
+            .map(|combo| KeyCombo::parse(combo, apple))
+            .collect::<Result<Vec<_>, _>>()?;
+        match combos.is_empty() {
+            true => Err(ParseError::Empty),
+            false => Ok(Self(combos)),
+        }
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = ParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, is_apple_platform())
+    }
+}
+
+/// matches key presses against bindings, keeping track of partially typed sequences.
+#[derive(Debug, Clone)]
+struct Matcher<A> {
+    bindings: Vec<(Hotkey, A)>,
+    sequence_timeout: f64,
+    pending: Vec<KeyCombo>,
+    last_time: f64,
+}
+
+enum Match<A> {
+    Action(A),
+    Partial,
+    None,
+}
+
+impl<A: Clone> Matcher<A> {
+    #[inline]
+    fn feed(&mut self, pressed: KeyCombo, time: f64) -> Match<A> {
+        if time - self.last_time > self.sequence_timeout {
+            self.pending.clear();
+        }
+        self.last_time = time;
+
+        let retry = !self.pending.is_empty();
+        self.pending.push(pressed.clone());
+        match self.lookup() {
+            Match::None if retry => {
+                // the pending sequence is broken. the key may start a new one.
+                self.pending.push(pressed);
+                self.lookup()
+            }
+            result => result,
+        }
+    }
+
+    #[inline]
+    fn lookup(&mut self) -> Match<A> {
+        let mut partial = false;
+        for (Hotkey(combos), action) in &self.bindings {
+            if combos.len() < self.pending.len() {
+                continue;
+            }
+            let prefix = combos
+                .iter()
+                .zip(&self.pending)
+                .all(|(combo, pressed)| combo.matches(pressed));
+            if !prefix {
+                continue;
+            }
+            if combos.len() == self.pending.len() {
+                self.pending.clear();
+                return Match::Action(action.clone());
+            }
+            partial = true;
+        }
+        match partial {
+            true => Match::Partial,
+            false => {
+                self.pending.clear();
+                Match::None
+            }
+        }
+    }
+}
+
+/// bindings from `Hotkey`s to actions.
+///
+/// ```ignore
+/// let actions = Keymap::new()
+///     .bind("Mod+S", Action::Save)
+///     .bind("Shift+?", Action::Help)
+///     .bind("g i", Action::GoToInbox)
+///     .listen(&document());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Keymap<A> {
+    bindings: Vec<(Hotkey, A)>,
+    sequence_timeout: i32,
+    in_text_inputs: bool,
+    prevent_default: bool,
+}
+
+impl<A> Default for Keymap<A> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            bindings: Vec::new(),
+            sequence_timeout: 1000,
+            in_text_inputs: false,
+            prevent_default: true,
+        }
+    }
+}
+
+impl<A: Clone + 'static> Keymap<A> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// when a binding is a prefix of another, the shorter one wins.
+    #[inline]
+    pub fn try_bind(mut self, hotkey: &str, action: A) -> Result<Self, ParseError> {
+        self.bindings.push((hotkey.parse()?, action));
+        Ok(self)
+    }
+
+    #[inline]
+    #[track_caller]
+    pub fn bind(self, hotkey: &str, action: A) -> Self {
+        self.try_bind(hotkey, action).unwrap_throw()
+    }
+
+    /// max milliseconds between the keys of a sequence. defaults to 1000.
+    #[inline]
+    pub fn sequence_timeout(mut self, millisecs: i32) -> Self {
+        self.sequence_timeout = millisecs;
+        self
+    }
+
+    /// also match while focus is in `<input>`, `<textarea>`, `<select>` or contenteditable elements.
+    #[inline]
+    pub fn in_text_inputs(mut self) -> Self {
+        self.in_text_inputs = true;
+        self
+    }
+
+    /// by default, `prevent_default` is called on matched and partially matched key presses.
+    #[inline]
+    pub fn allow_default(mut self) -> Self {
+        self.prevent_default = false;
+        self
+    }
+
+    /// listen on `target`, e.g. `document()` for global hotkeys or a `Node` for element-local ones.
+    /// the listener is removed when the stream is dropped.
+    #[inline]
+    pub fn listen(self, target: &impl AsRef<web_sys::EventTarget>) -> HotkeyStream<A> {
+        let Self {
+            bindings,
+            sequence_timeout,
+            in_text_inputs,
+            prevent_default,
+        } = self;
+        let mut matcher = Matcher {
+            bindings,
+            sequence_timeout: sequence_timeout as f64,
+            pending: Vec::new(),
+            last_time: f64::NEG_INFINITY,
+        };
+
+        let (tx, rx) = chan(usize::MAX);
+        let _binding = target
+            .as_ref()
+            .make_listener::<KeyDownEvent>()
+            .filter(move |event| {
+                !event.is_composing()
+                    && !is_modifier_key(&event.key())
+                    && (in_text_inputs || !is_text_input(event.target()))
+            })
+            .callback(move |event| {
+                let matched = matcher.feed(KeyCombo::from_event(&event), event.time_stamp());
+                if prevent_default && !matches!(matched, Match::None) {
+                    event.prevent_default();
+                }
+                if let Match::Action(action) = matched
+                    && let Err(err) = tx.send(action)
+                {
+                    crate::error!("fail to send: {}", err.to_string());
+                }
+            });
+
+        HotkeyStream { _binding, rx }
+    }
+}
+
+#[inline]
+fn is_modifier_key(key: &str) -> bool {
+    matches!(
+        key,
+        "Control" | "Alt" | "Shift" | "Meta" | "AltGraph" | "OS"
+    )
+}
+
+#[inline]
+fn is_text_input(target: Option<web_sys::EventTarget>) -> bool {
+    let Some(element) = target.and_then(|target| target.dyn_into::<web_sys::Element>().ok()) else {
+        return false;
+    };
+    matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+        || element
+            .dyn_ref::<web_sys::HtmlElement>()
+            .is_some_and(|element| element.is_content_editable())
+}
+
+/// yields the actions of matched hotkeys.
+pub struct HotkeyStream<A> {
+    _binding: DomEventBinding<KeyDownEvent>,
+    rx: chan::Receiver<A>,
+}
+
+impl<A> Stream for HotkeyStream<A> {
+    type Item = A;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn combo(s: &str) -> KeyCombo {
+        KeyCombo::parse(s, false).unwrap()
+    }
+
+    fn matcher(bindings: &[(&str, i32)]) -> Matcher<i32> {
+        Matcher {
+            bindings: bindings
+                .iter()
+                .map(|(hotkey, action)| (Hotkey::parse(hotkey, false).unwrap(), *action))
+                .collect(),
+            sequence_timeout: 1000.0,
+            pending: Vec::new(),
+            last_time: f64::NEG_INFINITY,
+        }
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn parse() {
+        let ctrl_s = combo("Ctrl+S");
+        assert!(ctrl_s.modifiers.ctrl && !ctrl_s.modifiers.shift);
+        assert_eq!(ctrl_s.key, "s");
+
+        assert_eq!(combo("Mod+S"), ctrl_s);
+        let cmd_s = KeyCombo::parse("Mod+S", true).unwrap();
+        assert!(cmd_s.modifiers.meta && !cmd_s.modifiers.ctrl);
+
+        assert_eq!(combo("Ctrl++").key, "+");
+        assert_eq!(combo("+").key, "+");
+        assert_eq!(combo("Esc").key, "escape");
+        assert_eq!(combo("space").key, " ");
+
+        assert_eq!(Hotkey::parse("g i", false).unwrap().0.len(), 2);
+        assert_eq!(Hotkey::parse(" ", false), Err(ParseError::Empty));
+        assert_eq!(
+            KeyCombo::parse("Hyper+K", false),
+            Err(ParseError::UnknownModifier("Hyper".into()))
+        );
+        assert_eq!(
+            KeyCombo::parse("Ctrl+", false),
+            Err(ParseError::MissingKey("Ctrl+".into()))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn matches() {
+        let shift_question = combo("Shift+?");
+        assert!(shift_question.matches(&combo("Shift+?")));
+        assert!(!shift_question.matches(&combo("?")));
+        // the symbol itself implies shift
+        assert!(combo("?").matches(&combo("Shift+?")));
+        assert!(!combo("s").matches(&combo("Shift+S")));
+        assert!(!combo("Ctrl+S").matches(&combo("Ctrl+Alt+S")));
+
+        // Alt and Shift change the produced key, fall back to `code`
+        let alt = Modifiers {
+            alt: true,
+            ..Modifiers::default()
+        };
+        let option_s = KeyCombo::pressed(alt, "ß", "KeyS");
+        assert_eq!(option_s.code.as_deref(), Some("s"));
+        assert!(combo("Alt+S").matches(&option_s));
+        assert!(!combo("S").matches(&option_s));
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        };
+        let shift_1 = KeyCombo::pressed(shift, "!", "Digit1");
+        assert!(combo("Shift+1").matches(&shift_1));
+        assert!(combo("!").matches(&shift_1));
+        assert!(!combo("1").matches(&shift_1));
+        assert_eq!(KeyCombo::pressed(shift, "S", "KeyS").code, None);
+        assert_eq!(
+            KeyCombo::pressed(Modifiers::default(), "ф", "KeyA").code,
+            None
+        );
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn sequence() {
+        let mut m = matcher(&[("g i", 1), ("g g", 2), ("Ctrl+S", 3)]);
+        assert!(matches!(m.feed(combo("g"), 0.0), Match::Partial));
+        assert!(matches!(m.feed(combo("i"), 100.0), Match::Action(1)));
+        assert!(matches!(m.feed(combo("Ctrl+S"), 200.0), Match::Action(3)));
+
+        // timeout
+        assert!(matches!(m.feed(combo("g"), 300.0), Match::Partial));
+        assert!(matches!(m.feed(combo("i"), 1400.0), Match::None));
+
+        // a broken sequence restarts with the current key
+        assert!(matches!(m.feed(combo("g"), 2000.0), Match::Partial));
+        assert!(matches!(m.feed(combo("x"), 2100.0), Match::None));
+        assert!(matches!(m.feed(combo("g"), 2200.0), Match::Partial));
+        assert!(matches!(m.feed(combo("Ctrl+S"), 2300.0), Match::Action(3)));
+        assert!(matches!(m.feed(combo("g"), 2400.0), Match::Partial));
+        assert!(matches!(m.feed(combo("g"), 2500.0), Match::Action(2)));
+    }
+}
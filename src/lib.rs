@@ -9,6 +9,12 @@ pub use spawn_ext::*;
 
 pub mod logger;
 
+mod error;
+pub use error::DomError;
+
+mod notify;
+pub use notify::{Notified, Notify};
+
 mod async_queue;
 pub use async_queue::AsyncQueue;
 
@@ -24,6 +30,9 @@ pub use state::*;
 mod scope;
 pub use scope::*;
 
+mod task;
+pub use task::JoinHandle;
+
 mod get;
 pub use get::*;
 
@@ -32,8 +41,22 @@ pub use node::*;
 mod event;
 pub use event::*;
 
+mod observer;
+pub use observer::*;
+mod gesture;
+pub use gesture::{Gesture, GestureOptions, GestureStream, SwipeDirection};
+
 mod timer;
 pub use timer::{Interval, Timeout};
 mod storage;
 pub use storage::Storage;
+mod cookie;
+pub use cookie::{CookieOptions, Cookies, SameSite};
 pub mod fetch;
+pub mod head;
+pub mod hotkey;
+pub mod router;
+pub mod url;
+
+#[cfg(test)]
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
@@ -1,4 +1,5 @@
 use super::Node;
+use crate::DomError;
 use std::borrow::Cow;
 use wasm_bindgen::prelude::*;
 
@@ -43,30 +44,36 @@ where
     #[inline]
     #[track_caller]
     pub fn add_class(&self, class: impl Into<Cow<'static, str>>) -> &Self {
-        self.as_ref()
-            .class_list()
-            .add_1(&class.into())
-            .unwrap_throw();
-        self
+        self.try_add_class(class).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_add_class(&self, class: impl Into<Cow<'static, str>>) -> Result<&Self, DomError> {
+        self.as_ref().class_list().add_1(&class.into())?;
+        Ok(self)
     }
 
     #[inline]
     #[track_caller]
     pub fn remove_class(&self, class: impl Into<Cow<'static, str>>) -> &Self {
-        self.as_ref()
-            .class_list()
-            .remove_1(&class.into())
-            .unwrap_throw();
-        self
+        self.try_remove_class(class).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_remove_class(&self, class: impl Into<Cow<'static, str>>) -> Result<&Self, DomError> {
+        self.as_ref().class_list().remove_1(&class.into())?;
+        Ok(self)
     }
 
     #[inline]
     #[track_caller]
     pub fn toggle_class(&self, class: impl Into<Cow<'static, str>>) -> bool {
-        self.as_ref()
-            .class_list()
-            .toggle(&class.into())
-            .unwrap_throw()
+        self.try_toggle_class(class).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_toggle_class(&self, class: impl Into<Cow<'static, str>>) -> Result<bool, DomError> {
+        Ok(self.as_ref().class_list().toggle(&class.into())?)
     }
 
     #[inline]
@@ -76,9 +83,7 @@ where
         name: impl Into<Cow<'static, str>>,
         value: impl Into<Cow<'static, str>>,
     ) -> Self {
-        self.as_ref()
-            .set_attribute(&name.into(), &value.into())
-            .unwrap_throw();
+        self.set_attr(name, value);
         self
     }
 
@@ -89,10 +94,17 @@ where
         name: impl Into<Cow<'static, str>>,
         value: impl Into<Cow<'static, str>>,
     ) -> &Self {
-        self.as_ref()
-            .set_attribute(&name.into(), &value.into())
-            .unwrap_throw();
-        self
+        self.try_set_attr(name, value).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_set_attr(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+    ) -> Result<&Self, DomError> {
+        self.as_ref().set_attribute(&name.into(), &value.into())?;
+        Ok(self)
     }
 
     #[inline]
@@ -108,10 +120,15 @@ where
     #[inline]
     #[track_caller]
     pub fn query_selector(&self, selector: &str) -> Vec<Node<web_sys::Node>> {
-        let node_list = self.as_ref().query_selector_all(selector).unwrap_throw();
+        self.try_query_selector(selector).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_query_selector(&self, selector: &str) -> Result<Vec<Node<web_sys::Node>>, DomError> {
+        let node_list = self.as_ref().query_selector_all(selector)?;
         let len = node_list.length();
-        (0..len)
+        Ok((0..len)
             .map(|i| Node(node_list.item(i).unwrap_throw()))
-            .collect()
+            .collect())
     }
 }
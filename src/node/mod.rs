@@ -43,15 +43,28 @@ impl<F: JsCast> Node<F> {
     }
 }
 
+use crate::Scope;
 use crate::event::{DomEvent, EventTargetExt};
 
 impl<T> Node<T>
 where
     T: AsRef<web_sys::EventTarget>,
 {
+    /// the listener is never removed. see `on_scoped`.
     #[inline]
     pub fn on<E: DomEvent>(self, f: impl FnMut(E::WebSysEvent) + 'static) -> Self {
-        self.listen::<E>(f);
+        std::mem::forget(self.listen::<E>(f));
+        self
+    }
+
+    /// the listener is removed when `scope` is cancelled or dropped.
+    #[inline]
+    pub fn on_scoped<E: DomEvent>(
+        self,
+        scope: &Scope,
+        f: impl FnMut(E::WebSysEvent) + 'static,
+    ) -> Self {
+        self.listen_scoped::<E>(scope, f);
         self
     }
 }
@@ -63,15 +76,25 @@ where
     #[inline]
     #[track_caller]
     pub fn child(self, child: impl AsRef<web_sys::Node>) -> Self {
-        self.as_ref().append_child(child.as_ref()).unwrap_throw();
-        self
+        self.try_child(child).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_child(self, child: impl AsRef<web_sys::Node>) -> Result<Self, crate::DomError> {
+        self.as_ref().append_child(child.as_ref())?;
+        Ok(self)
     }
 
     #[inline]
     #[track_caller]
     pub fn append(&self, child: impl AsRef<web_sys::Node>) -> &Self {
-        self.as_ref().append_child(child.as_ref()).unwrap_throw();
-        self
+        self.try_append(child).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_append(&self, child: impl AsRef<web_sys::Node>) -> Result<&Self, crate::DomError> {
+        self.as_ref().append_child(child.as_ref())?;
+        Ok(self)
     }
 }
 
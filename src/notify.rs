@@ -0,0 +1,251 @@
+use futures_lite::prelude::*;
+use slab::Slab;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll, Waker};
+
+/// single-threaded notification primitive.
+/// `Notified` futures resolve when notified, or when every `Notify` handle has been dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Notify {
+    inner: Rc<RefCell<NotifyInner>>,
+}
+
+#[derive(Debug, Default)]
+struct NotifyInner {
+    waiters: Slab<Waiter>,
+    // keys of `Waiting` waiters, oldest first. slab keys are reused, so their order is not the age.
+    queue: VecDeque<usize>,
+    permit: bool,
+}
+
+#[derive(Debug)]
+enum Waiter {
+    Waiting(Option<Waker>),
+    Notified { one: bool },
+}
+
+impl Drop for NotifyInner {
+    #[inline]
+    fn drop(&mut self) {
+        for (_, waiter) in &mut self.waiters {
+            if let Waiter::Waiting(Some(waker)) = waiter {
+                waker.wake_by_ref();
+            }
+        }
+    }
+}
+
+impl Notify {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// wake the oldest waiting `Notified`. if none is waiting, the next `Notified` resolves immediately.
+    #[inline]
+    pub fn notify_one(&self) {
+        let waker = {
+            let mut inner = self.inner.borrow_mut();
+            match inner.queue.pop_front() {
+                Some(key) => {
+                    match std::mem::replace(&mut inner.waiters[key], Waiter::Notified { one: true })
+                    {
+                        Waiter::Waiting(waker) => waker,
+                        Waiter::Notified { .. } => unreachable!(),
+                    }
+                }
+                None => {
+                    inner.permit = true;
+                    None
+                }
+            }
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// wake every `Notified` created before this call. no permit is stored.
+    #[inline]
+    pub fn notify_all(&self) {
+        let wakers = {
+            let mut inner = self.inner.borrow_mut();
+            inner.queue.clear();
+            inner
+                .waiters
+                .iter_mut()
+                .filter_map(|(_, waiter)| match waiter {
+                    Waiter::Waiting(_) => {
+                        match std::mem::replace(waiter, Waiter::Notified { one: false }) {
+                            Waiter::Waiting(waker) => waker,
+                            Waiter::Notified { .. } => unreachable!(),
+                        }
+                    }
+                    Waiter::Notified { .. } => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// the returned future is registered immediately, so it observes `notify_all` calls made before its first poll.
+    #[inline]
+    pub fn notified(&self) -> Notified {
+        let mut inner = self.inner.borrow_mut();
+        let waiter = match std::mem::take(&mut inner.permit) {
+            true => Waiter::Notified { one: true },
+            false => Waiter::Waiting(None),
+        };
+        let waiting = matches!(waiter, Waiter::Waiting(_));
+        let key = inner.waiters.insert(waiter);
+        if waiting {
+            inner.queue.push_back(key);
+        }
+        Notified {
+            inner: Rc::downgrade(&self.inner),
+            key: Some(key),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Notified {
+    inner: Weak<RefCell<NotifyInner>>,
+    // `None` once resolved
+    key: Option<usize>,
+}
+
+impl Future for Notified {
+    type Output = ();
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Some(key) = self.key else {
+            return Poll::Ready(());
+        };
+        let Some(inner) = self.inner.upgrade() else {
+            self.key.take();
+            return Poll::Ready(());
+        };
+        let mut inner = inner.borrow_mut();
+        match &mut inner.waiters[key] {
+            Waiter::Waiting(waker) => {
+                waker.replace(cx.waker().clone());
+                Poll::Pending
+            }
+            Waiter::Notified { .. } => {
+                inner.waiters.remove(key);
+                self.key.take();
+                Poll::Ready(())
+            }
+        }
+    }
+}
+
+impl Drop for Notified {
+    #[inline]
+    fn drop(&mut self) {
+        let Some(key) = self.key else {
+            return;
+        };
+        let Some(inner) = self.inner.upgrade() else {
+            return;
+        };
+        let waiter = {
+            let mut inner = inner.borrow_mut();
+            let waiter = inner.waiters.remove(key);
+            if let Waiter::Waiting(_) = waiter {
+                inner.queue.retain(|waiting| *waiting != key);
+            }
+            waiter
+        };
+        // pass an unconsumed notify_one on to another waiter
+        if let Waiter::Notified { one: true } = waiter {
+            Notify { inner }.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn notify_one() {
+        let notify = Notify::new();
+
+        // permit stored when nobody waits
+        notify.notify_one();
+        notify.notified().await;
+
+        let a = notify.notified();
+        let b = notify.notified();
+        notify.notify_one();
+        // dropping the notified waiter passes the notification on
+        drop(a);
+        b.await;
+
+        // the oldest waiter first, even when a newer one reuses a lower slab key
+        let a = notify.notified();
+        let mut b = notify.notified();
+        drop(a);
+        let mut c = notify.notified();
+        notify.notify_one();
+        assert!(futures_lite::future::poll_once(&mut c).await.is_none());
+        assert!(futures_lite::future::poll_once(&mut b).await.is_some());
+        notify.notify_one();
+        c.await;
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn notify_all() {
+        let notify = Notify::new();
+        let a = notify.notified();
+        let b = notify.notified();
+        notify.notify_all();
+        let c = notify.notified();
+        a.await;
+        b.await;
+
+        // `c` was created after notify_all, so it waits for the next notification
+        notify.notify_one();
+        c.await;
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn drop_order() {
+        // Notify dropped before Notified
+        let notify = Notify::new();
+        let notified = notify.notified();
+        drop(notify);
+        notified.await;
+
+        // Notified dropped before Notify
+        let notify = Notify::new();
+        drop(notify.notified());
+        notify.notify_all();
+        drop(notify);
+
+        // a clone keeps Notified pending
+        let notify = Notify::new();
+        let clone = notify.clone();
+        let mut notified = notify.notified();
+        drop(notify);
+        assert!(
+            futures_lite::future::poll_once(&mut notified)
+                .await
+                .is_none()
+        );
+        drop(clone);
+        notified.await;
+    }
+}
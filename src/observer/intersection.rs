@@ -0,0 +1,186 @@
+use crate::{DomError, Node, chan};
+use futures_lite::prelude::*;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Default)]
+pub struct IntersectionOptions {
+    root: Option<Node<web_sys::Element>>,
+    root_margin: Option<String>,
+    threshold: Vec<f64>,
+}
+
+impl IntersectionOptions {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// defaults to the viewport.
+    #[inline]
+    pub fn root(mut self, root: impl AsRef<web_sys::Element>) -> Self {
+        self.root.replace(Node(root.as_ref().clone()));
+        self
+    }
+
+    /// css margin syntax. e.g. "0px 0px 200px 0px"
+    #[inline]
+    pub fn root_margin(mut self, root_margin: impl Into<String>) -> Self {
+        self.root_margin.replace(root_margin.into());
+        self
+    }
+
+    /// ratios in 0.0..=1.0 at which an entry is emitted. defaults to [0.0].
+    #[inline]
+    pub fn threshold(mut self, threshold: impl IntoIterator<Item = f64>) -> Self {
+        self.threshold = threshold.into_iter().collect();
+        self
+    }
+
+    #[inline]
+    fn to_init(&self) -> web_sys::IntersectionObserverInit {
+        let init = web_sys::IntersectionObserverInit::new();
+        if let Some(root) = &self.root {
+            init.set_root(Some(root));
+        }
+        if let Some(root_margin) = &self.root_margin {
+            init.set_root_margin(root_margin);
+        }
+        if !self.threshold.is_empty() {
+            let threshold = self
+                .threshold
+                .iter()
+                .map(|&ratio| JsValue::from_f64(ratio))
+                .collect::<js_sys::Array>();
+            init.set_threshold(&threshold);
+        }
+        init
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntersectionEntry {
+    pub target: Node<web_sys::Element>,
+    pub is_intersecting: bool,
+    pub intersection_ratio: f64,
+    pub bounding_client_rect: web_sys::DomRectReadOnly,
+    pub intersection_rect: web_sys::DomRectReadOnly,
+    pub root_bounds: Option<web_sys::DomRectReadOnly>,
+    pub time: f64,
+}
+
+impl IntersectionEntry {
+    #[inline]
+    fn from_entry(entry: &web_sys::IntersectionObserverEntry) -> Self {
+        Self {
+            target: Node(entry.target()),
+            is_intersecting: entry.is_intersecting(),
+            intersection_ratio: entry.intersection_ratio(),
+            bounding_client_rect: entry.bounding_client_rect(),
+            intersection_rect: entry.intersection_rect(),
+            root_bounds: entry.root_bounds(),
+            time: entry.time(),
+        }
+    }
+}
+
+/// buffers up to 16 entries. when full, the oldest entry is dropped.
+pub struct IntersectionStream {
+    observer: web_sys::IntersectionObserver,
+    _closure: Closure<dyn FnMut(js_sys::Array)>,
+    rx: chan::Receiver<IntersectionEntry>,
+}
+
+impl Drop for IntersectionStream {
+    #[inline]
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+impl Stream for IntersectionStream {
+    type Item = IntersectionEntry;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next(cx)
+    }
+}
+
+impl<T> Node<T>
+where
+    T: AsRef<web_sys::Element>,
+{
+    /// fails with "SyntaxError" on an invalid root margin and "RangeError" on a threshold out of range.
+    #[inline]
+    pub fn try_observe_intersection(
+        &self,
+        options: IntersectionOptions,
+    ) -> Result<IntersectionStream, DomError> {
+        let (tx, rx) = chan(16);
+        let closure = Closure::new(move |entries: js_sys::Array| {
+            for entry in entries.iter() {
+                let entry = IntersectionEntry::from_entry(entry.unchecked_ref());
+                // a slow consumer still gets the latest entry
+                if let Err(err) = tx.force_send(entry) {
+                    crate::error!("fail to send: {}", err.to_string());
+                }
+            }
+        });
+
+        let observer = web_sys::IntersectionObserver::new_with_options(
+            closure.as_ref().unchecked_ref(),
+            &options.to_init(),
+        )?;
+        observer.observe(AsRef::<web_sys::Element>::as_ref(self));
+
+        Ok(IntersectionStream {
+            observer,
+            _closure: closure,
+            rx,
+        })
+    }
+
+    /// observe visibility of this element. the observer is disconnected when the stream is dropped.
+    #[inline]
+    #[track_caller]
+    pub fn observe_intersection(&self, options: IntersectionOptions) -> IntersectionStream {
+        self.try_observe_intersection(options).unwrap_throw()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Timeout;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn latest() {
+        let visible = "position: fixed; top: 0; width: 10px; height: 10px";
+        let hidden = "position: fixed; top: -1000px; width: 10px; height: 10px";
+        let element = Node(crate::document().create_element("div").unwrap());
+        element.set_attribute("style", visible).unwrap();
+        crate::body().append_child(&element).unwrap();
+        let mut entries = element.observe_intersection(IntersectionOptions::new());
+        assert!(entries.next().await.unwrap().is_intersecting);
+
+        // more changes than the buffer holds, without consuming them, ending hidden
+        for i in 0..=20 {
+            let style = match i % 2 {
+                0 => hidden,
+                _ => visible,
+            };
+            element.set_attribute("style", style).unwrap();
+            Timeout::new(50).await;
+        }
+        let mut last = None;
+        while let Some(Some(entry)) = futures_lite::future::poll_once(entries.next()).await {
+            last = Some(entry);
+        }
+        assert!(!last.unwrap().is_intersecting);
+        element.remove();
+    }
+}
@@ -0,0 +1,7 @@
+mod intersection;
+mod mutation;
+mod resize;
+
+pub use intersection::{IntersectionEntry, IntersectionOptions, IntersectionStream};
+pub use mutation::{Mutation, MutationObserverBuilder, MutationStream};
+pub use resize::{BoxSize, ResizeEntry, ResizeStream};
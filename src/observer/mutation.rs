@@ -0,0 +1,239 @@
+use crate::{DomError, Node, chan};
+use futures_lite::prelude::*;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    ChildList {
+        target: Node<web_sys::Node>,
+        added_nodes: Vec<Node<web_sys::Node>>,
+        removed_nodes: Vec<Node<web_sys::Node>>,
+        previous_sibling: Option<Node<web_sys::Node>>,
+        next_sibling: Option<Node<web_sys::Node>>,
+    },
+    Attributes {
+        target: Node<web_sys::Node>,
+        name: String,
+        namespace: Option<String>,
+        /// only available with `attribute_old_value()`
+        old_value: Option<String>,
+    },
+    CharacterData {
+        target: Node<web_sys::Node>,
+        /// only available with `character_data_old_value()`
+        old_value: Option<String>,
+    },
+}
+
+impl Mutation {
+    #[inline]
+    pub fn target(&self) -> &Node<web_sys::Node> {
+        match self {
+            Mutation::ChildList { target, .. } => target,
+            Mutation::Attributes { target, .. } => target,
+            Mutation::CharacterData { target, .. } => target,
+        }
+    }
+
+    #[inline]
+    fn from_record(record: &web_sys::MutationRecord) -> Option<Self> {
+        let target = Node(record.target()?);
+        match record.type_().as_str() {
+            "childList" => Some(Mutation::ChildList {
+                target,
+                added_nodes: node_list_to_vec(record.added_nodes()),
+                removed_nodes: node_list_to_vec(record.removed_nodes()),
+                previous_sibling: record.previous_sibling().map(Node),
+                next_sibling: record.next_sibling().map(Node),
+            }),
+            "attributes" => Some(Mutation::Attributes {
+                target,
+                name: record.attribute_name()?,
+                namespace: record.attribute_namespace(),
+                old_value: record.old_value(),
+            }),
+            "characterData" => Some(Mutation::CharacterData {
+                target,
+                old_value: record.old_value(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[inline]
+fn node_list_to_vec(node_list: web_sys::NodeList) -> Vec<Node<web_sys::Node>> {
+    (0..node_list.length())
+        .filter_map(|i| node_list.item(i))
+        .map(Node)
+        .collect()
+}
+
+pub struct MutationObserverBuilder {
+    target: web_sys::Node,
+    child_list: bool,
+    subtree: bool,
+    attributes: bool,
+    attribute_old_value: bool,
+    attribute_filter: Option<Vec<String>>,
+    character_data: bool,
+    character_data_old_value: bool,
+}
+
+impl MutationObserverBuilder {
+    #[inline]
+    pub(crate) fn new(target: web_sys::Node) -> Self {
+        Self {
+            target,
+            child_list: false,
+            subtree: false,
+            attributes: false,
+            attribute_old_value: false,
+            attribute_filter: None,
+            character_data: false,
+            character_data_old_value: false,
+        }
+    }
+
+    #[inline]
+    pub fn child_list(mut self) -> Self {
+        self.child_list = true;
+        self
+    }
+
+    #[inline]
+    pub fn subtree(mut self) -> Self {
+        self.subtree = true;
+        self
+    }
+
+    #[inline]
+    pub fn attributes(mut self) -> Self {
+        self.attributes = true;
+        self
+    }
+
+    /// implies `attributes()`
+    #[inline]
+    pub fn attribute_old_value(mut self) -> Self {
+        self.attributes = true;
+        self.attribute_old_value = true;
+        self
+    }
+
+    /// implies `attributes()`
+    #[inline]
+    pub fn attribute_filter<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.attributes = true;
+        self.attribute_filter
+            .replace(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    #[inline]
+    pub fn character_data(mut self) -> Self {
+        self.character_data = true;
+        self
+    }
+
+    /// implies `character_data()`
+    #[inline]
+    pub fn character_data_old_value(mut self) -> Self {
+        self.character_data = true;
+        self.character_data_old_value = true;
+        self
+    }
+
+    /// fails with "TypeError" when none of `child_list`, `attributes` or `character_data` is set.
+    #[inline]
+    pub fn try_into_stream(self) -> Result<MutationStream, DomError> {
+        let Self {
+            target,
+            child_list,
+            subtree,
+            attributes,
+            attribute_old_value,
+            attribute_filter,
+            character_data,
+            character_data_old_value,
+        } = self;
+
+        let (tx, rx) = chan(usize::MAX);
+        let closure = Closure::new(move |records: js_sys::Array| {
+            for record in records.iter() {
+                let record = record.unchecked_into::<web_sys::MutationRecord>();
+                let Some(mutation) = Mutation::from_record(&record) else {
+                    continue;
+                };
+                if let Err(err) = tx.send(mutation) {
+                    crate::error!("fail to send: {}", err.to_string());
+                }
+            }
+        });
+
+        let options = web_sys::MutationObserverInit::new();
+        options.set_child_list(child_list);
+        options.set_subtree(subtree);
+        options.set_attributes(attributes);
+        options.set_attribute_old_value(attribute_old_value);
+        if let Some(attribute_filter) = attribute_filter {
+            let array = attribute_filter
+                .iter()
+                .map(|name| JsValue::from_str(name))
+                .collect::<js_sys::Array>();
+            options.set_attribute_filter(&array);
+        }
+        options.set_character_data(character_data);
+        options.set_character_data_old_value(character_data_old_value);
+
+        let observer = web_sys::MutationObserver::new(closure.as_ref().unchecked_ref())?;
+        observer.observe_with_options(&target, &options)?;
+
+        Ok(MutationStream {
+            observer,
+            _closure: closure,
+            rx,
+        })
+    }
+
+    #[inline]
+    #[track_caller]
+    pub fn into_stream(self) -> MutationStream {
+        self.try_into_stream().unwrap_throw()
+    }
+}
+
+pub struct MutationStream {
+    observer: web_sys::MutationObserver,
+    _closure: Closure<dyn FnMut(js_sys::Array)>,
+    rx: chan::Receiver<Mutation>,
+}
+
+impl Drop for MutationStream {
+    #[inline]
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+impl Stream for MutationStream {
+    type Item = Mutation;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next(cx)
+    }
+}
+
+impl<T> Node<T>
+where
+    T: AsRef<web_sys::Node>,
+{
+    /// observe DOM mutations on this node. the observer is disconnected when the stream is dropped.
+    #[inline]
+    pub fn observe_mutations(&self) -> MutationObserverBuilder {
+        MutationObserverBuilder::new(AsRef::<web_sys::Node>::as_ref(self).clone())
+    }
+}
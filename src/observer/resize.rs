@@ -0,0 +1,147 @@
+use crate::{DomError, Node, chan};
+use futures_lite::prelude::*;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BoxSize {
+    pub inline_size: f64,
+    pub block_size: f64,
+}
+
+impl BoxSize {
+    #[inline]
+    fn from_sizes(sizes: js_sys::Array) -> Option<Self> {
+        let size = sizes
+            .get(0)
+            .dyn_into::<web_sys::ResizeObserverSize>()
+            .ok()?;
+        Some(Self {
+            inline_size: size.inline_size(),
+            block_size: size.block_size(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResizeEntry {
+    pub target: Node<web_sys::Element>,
+    pub content_box_size: BoxSize,
+    pub border_box_size: BoxSize,
+}
+
+impl ResizeEntry {
+    #[inline]
+    fn from_entry(entry: &web_sys::ResizeObserverEntry) -> Self {
+        // browsers without box size arrays only provide contentRect.
+        let content_rect = || {
+            let rect = entry.content_rect();
+            BoxSize {
+                inline_size: rect.width(),
+                block_size: rect.height(),
+            }
+        };
+        let content_box_size =
+            BoxSize::from_sizes(entry.content_box_size()).unwrap_or_else(content_rect);
+        let border_box_size =
+            BoxSize::from_sizes(entry.border_box_size()).unwrap_or(content_box_size);
+        Self {
+            target: Node(entry.target()),
+            content_box_size,
+            border_box_size,
+        }
+    }
+}
+
+/// buffers up to 16 entries. when full, the oldest entry is dropped.
+pub struct ResizeStream {
+    observer: web_sys::ResizeObserver,
+    _closure: Closure<dyn FnMut(js_sys::Array)>,
+    rx: chan::Receiver<ResizeEntry>,
+}
+
+impl Drop for ResizeStream {
+    #[inline]
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+impl Stream for ResizeStream {
+    type Item = ResizeEntry;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_next(cx)
+    }
+}
+
+impl<T> Node<T>
+where
+    T: AsRef<web_sys::Element>,
+{
+    #[inline]
+    pub fn try_observe_resize(&self) -> Result<ResizeStream, DomError> {
+        let (tx, rx) = chan(16);
+        let closure = Closure::new(move |entries: js_sys::Array| {
+            for entry in entries.iter() {
+                let entry = ResizeEntry::from_entry(entry.unchecked_ref());
+                // a slow consumer still gets the latest entry
+                if let Err(err) = tx.force_send(entry) {
+                    crate::error!("fail to send: {}", err.to_string());
+                }
+            }
+        });
+
+        let observer = web_sys::ResizeObserver::new(closure.as_ref().unchecked_ref())?;
+        observer.observe(AsRef::<web_sys::Element>::as_ref(self));
+
+        Ok(ResizeStream {
+            observer,
+            _closure: closure,
+            rx,
+        })
+    }
+
+    /// observe size changes of this element. the observer is disconnected when the stream is dropped.
+    #[inline]
+    #[track_caller]
+    pub fn observe_resize(&self) -> ResizeStream {
+        self.try_observe_resize().unwrap_throw()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Timeout;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn latest() {
+        let element = Node(crate::document().create_element("div").unwrap());
+        element
+            .set_attribute("style", "width: 1px; height: 1px")
+            .unwrap();
+        crate::body().append_child(&element).unwrap();
+        let mut sizes = element.observe_resize();
+        let first = sizes.next().await.unwrap();
+        assert_eq!(first.content_box_size.inline_size, 1.0);
+
+        // more changes than the buffer holds, without consuming them
+        for width in 2..=24 {
+            element
+                .set_attribute("style", &format!("width: {width}px; height: 1px"))
+                .unwrap();
+            Timeout::new(50).await;
+        }
+        let mut last = None;
+        while let Some(Some(entry)) = futures_lite::future::poll_once(sizes.next()).await {
+            last = Some(entry);
+        }
+        assert_eq!(last.unwrap().content_box_size.inline_size, 24.0);
+        element.remove();
+    }
+}
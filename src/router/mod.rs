@@ -0,0 +1,623 @@
+mod outlet;
+mod pattern;
+mod scroll;
+
+pub use pattern::{Params, Pattern};
+
+use crate::event::{ClickEvent, DomEventBinding, EventTargetExt, PopStateEvent};
+use crate::{DomError, Emitter, FutureSpawnExt, Node, Scope};
+use futures_lite::prelude::*;
+use scroll::ScrollTo;
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll, Waker};
+use wasm_bindgen::prelude::*;
+
+type LocalBoxFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+type Loader<R> = Box<dyn Fn(Params, Scope) -> LocalBoxFuture<Option<R>>>;
+type Guard = Box<dyn Fn(&str) -> LocalBoxFuture<GuardDecision>>;
+
+/// redirects followed before giving up on a navigation.
+const MAX_REDIRECTS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuardDecision {
+    Allow,
+    /// replace the current history entry with this path and route again.
+    Redirect(String),
+}
+
+/// guards, routes in declaration order and the fallback, independent of the DOM.
+struct Routes<R> {
+    guards: Vec<(Pattern, Guard)>,
+    routes: Vec<(Pattern, Loader<R>)>,
+    fallback: Box<dyn Fn(&str) -> R>,
+}
+
+impl<R> Routes<R> {
+    /// `Err` holds the path a guard redirects to.
+    #[inline]
+    async fn resolve(&self, path: &str, scope: &Scope) -> Result<R, String> {
+        for (pattern, guard) in &self.guards {
+            if pattern.matches(path).is_none() {
+                continue;
+            }
+            if let GuardDecision::Redirect(to) = guard(path).await {
+                return Err(to);
+            }
+        }
+        for (pattern, load) in &self.routes {
+            if let Some(params) = pattern.matches(path)
+                && let Some(route) = load(params, scope.clone()).await
+            {
+                return Ok(route);
+            }
+        }
+        Ok((self.fallback)(path))
+    }
+}
+
+pub struct RouterBuilder<R> {
+    routes: Routes<R>,
+    hash: bool,
+    intercept_links: bool,
+    scroll_restoration: bool,
+}
+
+impl<R: Clone + PartialEq + 'static> RouterBuilder<R> {
+    /// the first matching route whose `resolve` returns `Some` wins.
+    /// returning `None`, e.g. when a param does not parse, tries the next routes.
+    ///
+    /// ```ignore
+    /// .route("/users/:id", |params| Some(Page::User(params.get("id")?)))
+    /// ```
+    #[inline]
+    pub fn route(
+        self,
+        pattern: impl Into<Pattern>,
+        resolve: impl Fn(&Params) -> Option<R> + 'static,
+    ) -> Self {
+        self.route_with_loader(pattern, move |params, _| {
+            std::future::ready(resolve(&params))
+        })
+    }
+
+    /// like `route`, but the route is entered once `load` completes, e.g. with fetched data in `R`.
+    /// `scope` is the route's scope: it is cancelled when another navigation starts before `load` completes,
+    /// or when the route is left. tasks spawned on it end with the route.
+    #[inline]
+    pub fn route_with_loader<F>(
+        mut self,
+        pattern: impl Into<Pattern>,
+        load: impl Fn(Params, Scope) -> F + 'static,
+    ) -> Self
+    where
+        F: Future<Output = Option<R>> + 'static,
+    {
+        self.routes.routes.push((
+            pattern.into(),
+            Box::new(move |params, scope| Box::pin(load(params, scope))),
+        ));
+        self
+    }
+
+    /// run `guard` before entering paths matching `pattern`, e.g. "/admin/*rest".
+    /// guards run in registration order, until one redirects.
+    ///
+    /// ```ignore
+    /// .guard("/admin/*rest", |_| async {
+    ///     match is_signed_in().await {
+    ///         true => GuardDecision::Allow,
+    ///         false => GuardDecision::Redirect("/login".into()),
+    ///     }
+    /// })
+    /// ```
+    #[inline]
+    pub fn guard<F>(
+        mut self,
+        pattern: impl Into<Pattern>,
+        guard: impl Fn(&str) -> F + 'static,
+    ) -> Self
+    where
+        F: Future<Output = GuardDecision> + 'static,
+    {
+        self.routes
+            .guards
+            .push((pattern.into(), Box::new(move |path| Box::pin(guard(path)))));
+        self
+    }
+
+    /// route on the fragment, e.g. "index.html#/users/1", for hosts without server-side fallback.
+    #[inline]
+    pub fn hash_mode(mut self) -> Self {
+        self.hash = true;
+        self
+    }
+
+    /// whether clicks on same-origin `<a href>` navigate without reloading. defaults to true.
+    /// links with `target`, `download` or `rel="external"`, and modified clicks are left alone.
+    #[inline]
+    pub fn intercept_links(mut self, value: bool) -> Self {
+        self.intercept_links = value;
+        self
+    }
+
+    /// whether to save the scroll position in history state and restore it on back/forward.
+    /// `navigate` scrolls to the top, or to the element targeted by the fragment. defaults to true.
+    #[inline]
+    pub fn scroll_restoration(mut self, value: bool) -> Self {
+        self.scroll_restoration = value;
+        self
+    }
+
+    /// the initial route is entered right away unless a guard or loader has to wait.
+    #[inline]
+    pub fn build(self) -> Router<R> {
+        let Self {
+            routes,
+            hash,
+            intercept_links,
+            scroll_restoration,
+        } = self;
+
+        let inner = Rc::new_cyclic(|weak: &Weak<RouterInner<R>>| {
+            let popstate = crate::window().make_listener::<PopStateEvent>().callback({
+                let weak = weak.clone();
+                move |event| {
+                    if let Some(inner) = weak.upgrade() {
+                        Router { inner }.start(ScrollTo::saved(&event.state()));
+                    }
+                }
+            });
+            // in hash mode, the browser handles links to "#/..." and fires popstate.
+            let links = (intercept_links && !hash).then(|| {
+                crate::document()
+                    .make_delegated_listener::<ClickEvent>("a[href]")
+                    .callback({
+                        let weak = weak.clone();
+                        move |event, anchor| {
+                            let Some(href) = internal_href(&event, anchor) else {
+                                return;
+                            };
+                            let Some(inner) = weak.upgrade() else {
+                                return;
+                            };
+                            event.prevent_default();
+                            Router { inner }.navigate(&href);
+                        }
+                    })
+            });
+            RouterInner {
+                routes,
+                hash,
+                scroll_restoration,
+                scope: Scope::new(),
+                path: RefCell::new(String::new()),
+                current: RefCell::new(None),
+                active: RefCell::new(None),
+                pending: RefCell::new(None),
+                emitter: Emitter::new(usize::MAX),
+                _popstate: popstate,
+                _links: links,
+            }
+        });
+        if scroll_restoration {
+            scroll::track(&inner.scope);
+        }
+
+        let router = Router { inner };
+        router.start(ScrollTo::Keep);
+        router
+    }
+}
+
+struct RouterInner<R> {
+    routes: Routes<R>,
+    hash: bool,
+    scroll_restoration: bool,
+    // parent of route scopes, dropped with the router
+    scope: Scope,
+    path: RefCell<String>,
+    current: RefCell<Option<R>>,
+    active: RefCell<Option<Scope>>,
+    pending: RefCell<Option<Scope>>,
+    emitter: Emitter<R>,
+    _popstate: DomEventBinding<PopStateEvent>,
+    _links: Option<DomEventBinding<ClickEvent>>,
+}
+
+/// matches the location against routes and keeps the active route up to date.
+/// listeners are removed and route scopes cancelled when the last clone is dropped.
+pub struct Router<R> {
+    inner: Rc<RouterInner<R>>,
+}
+
+impl<R> Clone for Router<R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<R: Clone + PartialEq + 'static> Router<R> {
+    /// `fallback` builds the route for paths no route matches, e.g. a 404 page.
+    #[inline]
+    pub fn builder(fallback: impl Fn(&str) -> R + 'static) -> RouterBuilder<R> {
+        RouterBuilder {
+            routes: Routes {
+                guards: Vec::new(),
+                routes: Vec::new(),
+                fallback: Box::new(fallback),
+            },
+            hash: false,
+            intercept_links: true,
+            scroll_restoration: true,
+        }
+    }
+
+    /// `None` until the first navigation completes.
+    #[inline]
+    pub fn current(&self) -> Option<R> {
+        self.inner.current.borrow().clone()
+    }
+
+    /// the path of the active route, including query and fragment.
+    #[inline]
+    pub fn path(&self) -> String {
+        self.inner.path.borrow().clone()
+    }
+
+    /// the scope of the active route, cancelled when the route is left.
+    #[inline]
+    pub fn scope(&self) -> Option<Scope> {
+        self.inner.active.borrow().clone()
+    }
+
+    /// whether a navigation waits for guards or loaders.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        self.inner.pending.borrow().is_some()
+    }
+
+    /// yields the active route whenever it changes.
+    #[inline]
+    pub fn watch(&self) -> impl Stream<Item = R> + use<R> {
+        self.inner.emitter.receive()
+    }
+
+    /// the URL to put in `href` for `path`, e.g. "#/users/1" in hash mode.
+    #[inline]
+    pub fn href(&self, path: &str) -> String {
+        match self.inner.hash {
+            true => format!("#{path}"),
+            false => path.into(),
+        }
+    }
+
+    /// push a history entry for `path` and route to it.
+    #[inline]
+    #[track_caller]
+    pub fn navigate(&self, path: &str) {
+        self.try_navigate(path).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_navigate(&self, path: &str) -> Result<(), DomError> {
+        if self.inner.scroll_restoration {
+            scroll::save();
+        }
+        crate::history().push_state_with_url(&JsValue::NULL, "", Some(&self.href(path)))?;
+        self.start(ScrollTo::Top);
+        Ok(())
+    }
+
+    /// like `navigate`, but replace the current history entry.
+    #[inline]
+    #[track_caller]
+    pub fn replace(&self, path: &str) {
+        self.try_replace(path).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_replace(&self, path: &str) -> Result<(), DomError> {
+        let history = crate::history();
+        history.replace_state_with_url(&history.state()?, "", Some(&self.href(path)))?;
+        self.start(ScrollTo::Keep);
+        Ok(())
+    }
+
+    /// route the location again, e.g. after changing history directly.
+    #[inline]
+    pub fn sync(&self) {
+        self.start(ScrollTo::Keep);
+    }
+
+    /// supersede any pending navigation and route the current location.
+    #[inline]
+    fn start(&self, scroll: ScrollTo) {
+        crate::url::location_changed();
+        let scope = self.inner.scope.child();
+        if let Some(pending) = self.inner.pending.replace(Some(scope.clone())) {
+            pending.cancel("navigation superseded");
+        }
+
+        let router = self.clone();
+        let mut navigation = Box::pin(scope.wrap_future({
+            let router = self.clone();
+            let scope = scope.clone();
+            async move { router.resolve(&scope).await }
+        }));
+        let finish = move |resolved: Option<Option<(String, R)>>| {
+            match resolved {
+                Some(Some((path, route))) => router.enter(scope, path, route, scroll),
+                // superseded
+                None => {}
+                Some(None) => {
+                    router.inner.pending.take();
+                }
+            }
+        };
+        // enter synchronously when nothing has to wait
+        match navigation
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(resolved) => finish(resolved),
+            Poll::Pending => async move { finish(navigation.await) }.spawn(),
+        }
+    }
+
+    /// follow redirects until a route is found. `None` on failure, which is logged.
+    #[inline]
+    async fn resolve(&self, scope: &Scope) -> Option<(String, R)> {
+        for _ in 0..MAX_REDIRECTS {
+            let path = current_path(self.inner.hash);
+            match self.inner.routes.resolve(&path, scope).await {
+                Ok(route) => return Some((path, route)),
+                Err(to) => {
+                    // keep the scroll position saved in the entry
+                    let history = crate::history();
+                    let replaced = history.state().and_then(|state| {
+                        history.replace_state_with_url(&state, "", Some(&self.href(&to)))
+                    });
+                    if let Err(err) = replaced {
+                        crate::error!("{}", DomError::from(err));
+                        return None;
+                    }
+                }
+            }
+        }
+        crate::error!("too many redirects from {}", current_path(self.inner.hash));
+        None
+    }
+
+    #[inline]
+    fn enter(&self, scope: Scope, path: String, route: R, scroll: ScrollTo) {
+        self.inner.pending.take();
+        if let Some(left) = self.inner.active.replace(Some(scope.clone())) {
+            left.cancel("route left");
+        }
+        if self.inner.scroll_restoration {
+            let fragment = path
+                .split_once('#')
+                .map(|(_, fragment)| crate::url::percent_decode(fragment));
+            scroll.apply(&scope, fragment);
+        }
+        *self.inner.path.borrow_mut() = path;
+        let old_route = self.inner.current.replace(Some(route.clone()));
+        if old_route.as_ref() != Some(&route) {
+            self.inner.emitter.emit(route);
+        }
+    }
+}
+
+#[inline]
+fn current_path(hash: bool) -> String {
+    let location = crate::location();
+    match hash {
+        true => {
+            let hash = location.hash().unwrap_or_default();
+            match hash.strip_prefix('#') {
+                Some(path) if path.starts_with('/') => path.into(),
+                _ => "/".into(),
+            }
+        }
+        false => {
+            let path = location.pathname().unwrap_or_default();
+            let search = location.search().unwrap_or_default();
+            let fragment = location.hash().unwrap_or_default();
+            format!("{path}{search}{fragment}")
+        }
+    }
+}
+
+/// the path to route to for a click on `anchor`, or `None` to let the browser handle it.
+#[inline]
+fn internal_href(event: &web_sys::MouseEvent, anchor: Node<web_sys::Element>) -> Option<String> {
+    if event.default_prevented()
+        || event.button() != 0
+        || event.ctrl_key()
+        || event.meta_key()
+        || event.shift_key()
+        || event.alt_key()
+    {
+        return None;
+    }
+    // e.g. svg links
+    let anchor = anchor.dyn_into::<web_sys::HtmlAnchorElement>().ok()?;
+    let external = anchor
+        .get_attribute("rel")
+        .is_some_and(|rel| rel.split_whitespace().any(|rel| rel == "external"));
+    if !matches!(anchor.target().as_str(), "" | "_self")
+        || anchor.has_attribute("download")
+        || external
+    {
+        return None;
+    }
+
+    let location = crate::location();
+    if anchor.origin() != location.origin().ok()? {
+        return None;
+    }
+    let (path, search, fragment) = (anchor.pathname(), anchor.search(), anchor.hash());
+    // in-page anchors keep scrolling natively
+    if !fragment.is_empty()
+        && Some(&path) == location.pathname().ok().as_ref()
+        && Some(&search) == location.search().ok().as_ref()
+    {
+        return None;
+    }
+    Some(format!("{path}{search}{fragment}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Page {
+        Home,
+        User(u32),
+        NotFound(String),
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn resolve() {
+        let routes = Routes {
+            guards: vec![(
+                Pattern::new("/users/:id"),
+                Box::new(|path: &str| {
+                    let decision = match path {
+                        "/users/0" => GuardDecision::Redirect("/".into()),
+                        _ => GuardDecision::Allow,
+                    };
+                    Box::pin(std::future::ready(decision)) as LocalBoxFuture<_>
+                }) as Guard,
+            )],
+            routes: vec![
+                (
+                    Pattern::new("/"),
+                    Box::new(|_, _| {
+                        Box::pin(std::future::ready(Some(Page::Home))) as LocalBoxFuture<_>
+                    }) as Loader<Page>,
+                ),
+                (
+                    Pattern::new("/users/:id"),
+                    Box::new(|params: Params, _| {
+                        Box::pin(async move { Some(Page::User(params.get("id")?)) })
+                            as LocalBoxFuture<_>
+                    }),
+                ),
+            ],
+            fallback: Box::new(|path| Page::NotFound(path.into())),
+        };
+        let scope = Scope::new();
+        assert_eq!(routes.resolve("/", &scope).await, Ok(Page::Home));
+        assert_eq!(routes.resolve("/users/7", &scope).await, Ok(Page::User(7)));
+        assert_eq!(routes.resolve("/users/0", &scope).await, Err("/".into()));
+        // unparsable params fall through
+        assert_eq!(
+            routes.resolve("/users/me", &scope).await,
+            Ok(Page::NotFound("/users/me".into()))
+        );
+    }
+
+    fn router() -> Router<Page> {
+        Router::builder(|path| Page::NotFound(path.into()))
+            .route("/", |_| Some(Page::Home))
+            .route("/users/:id", |params| Some(Page::User(params.get("id")?)))
+            .guard("/users/:id", |path| {
+                let decision = match path {
+                    "/users/0" => GuardDecision::Redirect("/".into()),
+                    _ => GuardDecision::Allow,
+                };
+                std::future::ready(decision)
+            })
+            .intercept_links(false)
+            .build()
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn navigate() {
+        let history = crate::history();
+        let original = crate::location().href().unwrap();
+        let router = router();
+
+        router.navigate("/users/7");
+        assert_eq!(router.current(), Some(Page::User(7)));
+        assert_eq!(router.path(), "/users/7");
+        assert_eq!(crate::location().pathname().unwrap(), "/users/7");
+        router.navigate("/missing?a=1");
+        assert_eq!(
+            router.current(),
+            Some(Page::NotFound("/missing?a=1".into()))
+        );
+
+        // a guard redirect replaces the entry, keeping its state
+        let state = js_sys::Object::new();
+        history.replace_state(&state, "").unwrap();
+        router.replace("/users/0");
+        assert_eq!(router.current(), Some(Page::Home));
+        assert_eq!(router.path(), "/");
+        assert_eq!(history.state().unwrap(), JsValue::from(state));
+
+        drop(router);
+        history
+            .replace_state_with_url(&JsValue::NULL, "", Some(&original))
+            .unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn outlet() {
+        let original = crate::location().href().unwrap();
+        let router = router();
+        router.navigate("/");
+        let outlet = crate::document().create_element("div").unwrap();
+        let scope = Scope::new();
+        let scopes = Rc::new(RefCell::new(Vec::new()));
+        router.outlet(&outlet, &scope, std::mem::discriminant, {
+            let scopes = scopes.clone();
+            move |page, scope| {
+                scopes.borrow_mut().push(scope.clone());
+                let node = crate::document().create_element("span").unwrap();
+                node.set_text_content(Some(&format!("{page:?}")));
+                Some(node)
+            }
+        });
+        let tick = || crate::Timeout::new(0);
+        tick().await;
+        assert_eq!(outlet.text_content().unwrap(), "Home");
+
+        router.navigate("/users/1");
+        tick().await;
+        assert_eq!(outlet.text_content().unwrap(), "User(1)");
+        assert_eq!(
+            scopes.borrow()[0].cancel_reason(),
+            Some("route left".into())
+        );
+
+        // same key, no re-render
+        router.navigate("/users/2");
+        tick().await;
+        assert_eq!(outlet.text_content().unwrap(), "User(1)");
+        assert_eq!(scopes.borrow().len(), 2);
+
+        router.navigate("/");
+        tick().await;
+        assert_eq!(outlet.text_content().unwrap(), "Home");
+        assert_eq!(scopes.borrow().len(), 3);
+
+        drop(scope);
+        drop(router);
+        crate::history()
+            .replace_state_with_url(&JsValue::NULL, "", Some(&original))
+            .unwrap();
+    }
+}
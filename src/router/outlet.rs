@@ -0,0 +1,67 @@
+use super::Router;
+use crate::{JoinHandle, Scope};
+use futures_lite::prelude::*;
+
+impl<R: Clone + PartialEq + 'static> Router<R> {
+    /// keep the children of `outlet` in sync with the active route until `scope` ends.
+    ///
+    /// `render` runs whenever `key` of the active route changes, with a scope that is cancelled on the next render.
+    /// returning `None` leaves the outlet empty. for nested layouts, a layout keyed on its section renders
+    /// its own outlet with the scope it is given, so it survives navigation inside the section.
+    ///
+    /// ```ignore
+    /// router.outlet(&app, &scope, |page| matches!(page, Page::Admin(_)), move |page, scope| match page {
+    ///     Page::Admin(_) => {
+    ///         let content = html::div();
+    ///         router.outlet(&content, scope, Page::clone, render_admin_page);
+    ///         Some(html::div().child(admin_nav()).child(content))
+    ///     }
+    ///     page => Some(render_page(page)),
+    /// });
+    /// ```
+    #[inline]
+    pub fn outlet<K, N>(
+        &self,
+        outlet: &impl AsRef<web_sys::Node>,
+        scope: &Scope,
+        key: impl Fn(&R) -> K + 'static,
+        mut render: impl FnMut(&R, &Scope) -> Option<N> + 'static,
+    ) -> JoinHandle<()>
+    where
+        K: PartialEq + 'static,
+        N: AsRef<web_sys::Node>,
+    {
+        let outlet = outlet.as_ref().clone();
+        // the task is owned by `scope`, so it must not keep `scope` itself alive
+        let outlet_scope = scope.child();
+        let mut rendered: Option<(K, Scope)> = None;
+        let mut update = move |route: &R| {
+            let key = key(route);
+            if rendered.as_ref().is_some_and(|(last, _)| *last == key) {
+                return;
+            }
+            if let Some((_, scope)) = rendered.take() {
+                scope.cancel("route left");
+            }
+            let render_scope = outlet_scope.child();
+            outlet.set_text_content(None);
+            if let Some(node) = render(route, &render_scope)
+                && let Err(err) = outlet.append_child(node.as_ref())
+            {
+                crate::error!("{}", crate::DomError::from(err));
+            }
+            rendered = Some((key, render_scope));
+        };
+
+        let router = self.clone();
+        let mut changes = self.watch();
+        scope.spawn(async move {
+            if let Some(route) = router.current() {
+                update(&route);
+            }
+            while let Some(route) = changes.next().await {
+                update(&route);
+            }
+        })
+    }
+}
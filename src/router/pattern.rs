@@ -0,0 +1,126 @@
+use crate::url::percent_decode;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Static(String),
+    Param(String),
+    Rest(String),
+}
+
+/// a path pattern such as "/", "/users/:id" or "/files/*path".
+/// `:name` matches one segment, `*name` matches the remaining segments and must come last.
+/// empty segments are ignored, so trailing slashes do not matter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    segments: Vec<Segment>,
+}
+
+impl Pattern {
+    #[inline]
+    pub fn new(pattern: &str) -> Self {
+        let segments = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| {
+                if let Some(name) = segment.strip_prefix(':') {
+                    Segment::Param(name.into())
+                } else if let Some(name) = segment.strip_prefix('*') {
+                    Segment::Rest(name.into())
+                } else {
+                    Segment::Static(segment.into())
+                }
+            })
+            .collect();
+        Self { segments }
+    }
+
+    /// query and fragment of `path` are ignored. params are percent-decoded.
+    #[inline]
+    pub fn matches(&self, path: &str) -> Option<Params> {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let mut rest = path.split('/').filter(|segment| !segment.is_empty());
+        let mut params = Params::default();
+        for segment in &self.segments {
+            match segment {
+                Segment::Static(expected) => {
+                    if percent_decode(rest.next()?) != *expected {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => {
+                    params.0.push((name.clone(), percent_decode(rest.next()?)));
+                }
+                Segment::Rest(name) => {
+                    let rest = rest.by_ref().map(percent_decode).collect::<Vec<_>>();
+                    params.0.push((name.clone(), rest.join("/")));
+                }
+            }
+        }
+        match rest.next() {
+            Some(_) => None,
+            None => Some(params),
+        }
+    }
+}
+
+impl From<&str> for Pattern {
+    #[inline]
+    fn from(pattern: &str) -> Self {
+        Self::new(pattern)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params(Vec<(String, String)>);
+
+impl Params {
+    /// parse a param, e.g. `params.get::<u32>("id")`. `None` if missing or unparsable.
+    #[inline]
+    pub fn get<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get_str(name)?.parse().ok()
+    }
+
+    #[inline]
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn matches() {
+        let root = Pattern::new("/");
+        assert!(root.matches("/").is_some());
+        assert!(root.matches("").is_some());
+        assert!(root.matches("/users").is_none());
+
+        let user = Pattern::new("/users/:id");
+        let params = user.matches("/users/42/?tab=posts").unwrap();
+        assert_eq!(params.get::<u32>("id"), Some(42));
+        assert_eq!(params.get::<u32>("name"), None);
+        assert!(user.matches("/users").is_none());
+        assert!(user.matches("/users/42/posts").is_none());
+        assert!(user.matches("/groups/42").is_none());
+
+        let files = Pattern::new("/files/*path");
+        let params = files.matches("/files/a%20b/c.txt#top").unwrap();
+        assert_eq!(params.get_str("path"), Some("a b/c.txt"));
+        assert_eq!(files.matches("/files").unwrap().get_str("path"), Some(""));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{Scope, Timeout, scroll_position};
+use futures_lite::prelude::*;
+use wasm_bindgen::prelude::*;
+
+const SCROLL_X: &str = "mozkitScrollX";
+const SCROLL_Y: &str = "mozkitScrollY";
+
+/// where to scroll after entering a route.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum ScrollTo {
+    Keep,
+    /// the top, or the element targeted by the fragment.
+    Top,
+    Position(f64, f64),
+}
+
+impl ScrollTo {
+    /// the position saved in a history entry's state.
+    #[inline]
+    pub(super) fn saved(state: &JsValue) -> Self {
+        let get = |key| {
+            js_sys::Reflect::get(state, &JsValue::from_str(key))
+                .ok()
+                .and_then(|value| value.as_f64())
+        };
+        match (state.is_object(), get(SCROLL_X), get(SCROLL_Y)) {
+            (true, Some(x), Some(y)) => Self::Position(x, y),
+            _ => Self::Keep,
+        }
+    }
+
+    /// once the tasks rendering the new route had a chance to run.
+    #[inline]
+    pub(super) fn apply(self, scope: &Scope, fragment: Option<String>) {
+        if self == Self::Keep {
+            return;
+        }
+        scope.spawn(async move {
+            Timeout::new(0).await;
+            let window = crate::window();
+            match self {
+                Self::Keep => {}
+                Self::Top => match fragment.and_then(|id| crate::document().get_element_by_id(&id))
+                {
+                    Some(element) => element.scroll_into_view(),
+                    None => window.scroll_to_with_x_and_y(0.0, 0.0),
+                },
+                Self::Position(x, y) => window.scroll_to_with_x_and_y(x, y),
+            }
+        });
+    }
+}
+
+/// record the scroll position in the current history entry, keeping other state.
+#[inline]
+pub(super) fn save() {
+    let history = crate::history();
+    let state = js_sys::Object::new();
+    if let Ok(previous) = history.state()
+        && previous.is_object()
+    {
+        js_sys::Object::assign(&state, previous.unchecked_ref());
+    }
+    let window = crate::window();
+    let set = |key, value: Result<f64, JsValue>| {
+        let value = JsValue::from_f64(value.unwrap_or_default());
+        let _ = js_sys::Reflect::set(&state, &JsValue::from_str(key), &value);
+    };
+    set(SCROLL_X, window.scroll_x());
+    set(SCROLL_Y, window.scroll_y());
+    if let Err(err) = history.replace_state(&state, "") {
+        crate::error!("{}", crate::DomError::from(err));
+    }
+}
+
+/// keep the saved position up to date while `scope` lives, so that it survives back/forward.
+/// throttled, as browsers rate-limit `replaceState`.
+#[inline]
+pub(super) fn track(scope: &Scope) {
+    let _ = crate::history().set_scroll_restoration(web_sys::ScrollRestoration::Manual);
+    let mut positions = scroll_position();
+    scope.spawn(async move {
+        while positions.next().await.is_some() {
+            save();
+            Timeout::new(500).await;
+        }
+    });
+}
@@ -1,9 +1,11 @@
+use crate::{DomError, JoinHandle, Node, Notified, Notify, document};
 use futures_lite::prelude::*;
 use slab::Slab;
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::pin::Pin;
 use std::rc::{Rc, Weak};
-use std::task::{Context, Poll, Waker};
+use std::task::{Context, Poll};
 use wasm_bindgen::prelude::*;
 
 #[derive(Debug, Clone, Default)]
@@ -11,51 +13,255 @@ pub struct Scope {
     inner: Rc<RefCell<ScopeInner>>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CancelReason {
+    /// every handle of the scope (or of its parent) was dropped before it was cancelled.
+    Dropped,
+    /// the node the scope is bound to was removed from the document. see `Scope::bound_to`.
+    Detached,
+    Custom(Cow<'static, str>),
+}
+
+impl From<&'static str> for CancelReason {
+    #[inline]
+    fn from(reason: &'static str) -> Self {
+        Self::Custom(reason.into())
+    }
+}
+
+impl From<String> for CancelReason {
+    #[inline]
+    fn from(reason: String) -> Self {
+        Self::Custom(reason.into())
+    }
+}
+
+impl std::fmt::Display for CancelReason {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CancelReason::Dropped => write!(f, "scope dropped"),
+            CancelReason::Detached => write!(f, "node detached"),
+            CancelReason::Custom(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct ScopeInner {
-    wakers: Slab<Weak<RefCell<Option<Waker>>>>,
+    notify: Notify,
+    children: Slab<Weak<RefCell<ScopeInner>>>,
+    parent: Option<(Weak<RefCell<ScopeInner>>, usize)>,
+    /// shared with `Cancelled`, which outlives the scope when it is cancelled and then dropped.
+    cancelled: Rc<RefCell<Option<CancelReason>>>,
+    cleanups: Cleanups,
+}
+
+#[derive(Default)]
+struct Cleanups(Vec<Box<dyn FnOnce()>>);
+
+impl Cleanups {
+    /// run in reverse registration order.
+    #[inline]
+    fn run(self) {
+        for cleanup in self.0.into_iter().rev() {
+            cleanup();
+        }
+    }
+}
+
+impl std::fmt::Debug for Cleanups {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cleanups({})", self.0.len())
+    }
+}
+
+impl ScopeInner {
+    #[inline]
+    fn take_children(&mut self) -> Vec<Rc<RefCell<ScopeInner>>> {
+        let children = self
+            .children
+            .drain()
+            .filter_map(|child| child.upgrade())
+            .collect::<Vec<_>>();
+        for child in &children {
+            child.borrow_mut().parent.take();
+        }
+        children
+    }
 }
 
 impl Drop for ScopeInner {
     #[inline]
     fn drop(&mut self) {
-        for (_, waker) in &self.wakers {
-            let waker = waker.upgrade().unwrap_throw();
-            let mut waker = waker.borrow_mut();
-            if let Some(waker) = waker.take() {
-                waker.wake();
-            }
+        // pending `Cancelled` futures are woken by dropping `notify`, as they hold only weak references.
+        for child in self.take_children() {
+            Scope { inner: child }.cancel(CancelReason::Dropped);
+        }
+        if let Some((parent, key)) = self.parent.take()
+            && let Some(parent) = parent.upgrade()
+        {
+            parent.borrow_mut().children.try_remove(key);
         }
+        std::mem::take(&mut self.cleanups).run();
     }
 }
 
+/// node of a scope created by `Scope::bound_to`.
 #[derive(Debug)]
-struct ScopeDropFuture {
+struct BoundNode {
+    node: web_sys::Node,
+    connected: bool,
     scope: Weak<RefCell<ScopeInner>>,
-    waker: Rc<RefCell<Option<Waker>>>,
-    waker_index: usize,
 }
 
-impl Future for ScopeDropFuture {
-    type Output = ();
+/// a single observer shared by every bound scope, created by the first one.
+/// it is disconnected while no scope is bound.
+struct Detector {
+    observer: web_sys::MutationObserver,
+    _closure: Closure<dyn FnMut(js_sys::Array)>,
+    nodes: Slab<BoundNode>,
+}
+
+thread_local! {
+    static DETECTOR: RefCell<Option<Detector>> = const { RefCell::new(None) };
+}
 
+impl Detector {
     #[inline]
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match self.scope.upgrade().is_some() {
-            true => {
-                self.waker.borrow_mut().replace(cx.waker().clone());
-                Poll::Pending
+    fn new() -> Result<Self, DomError> {
+        let closure = Closure::new(|_: js_sys::Array| Self::check());
+        let observer = web_sys::MutationObserver::new(closure.as_ref().unchecked_ref())?;
+        Ok(Self {
+            observer,
+            _closure: closure,
+            nodes: Slab::new(),
+        })
+    }
+
+    #[inline]
+    fn observe(
+        observer: &web_sys::MutationObserver,
+        target: &web_sys::Node,
+    ) -> Result<(), DomError> {
+        let options = web_sys::MutationObserverInit::new();
+        options.set_child_list(true);
+        options.set_subtree(true);
+        Ok(observer.observe_with_options(target, &options)?)
+    }
+
+    /// observe the shadow roots `node` is in, as removals inside them do not reach the document.
+    #[inline]
+    fn observe_roots(
+        observer: &web_sys::MutationObserver,
+        node: &web_sys::Node,
+    ) -> Result<(), DomError> {
+        let mut root = node.get_root_node();
+        while let Ok(shadow) = root.dyn_into::<web_sys::ShadowRoot>() {
+            Self::observe(observer, &shadow)?;
+            root = shadow.host().get_root_node();
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn register(node: web_sys::Node, scope: Weak<RefCell<ScopeInner>>) -> Result<usize, DomError> {
+        DETECTOR.with(|detector| {
+            let mut detector = detector.borrow_mut();
+            if detector.is_none() {
+                detector.replace(Self::new()?);
+            }
+            let Some(Detector {
+                observer, nodes, ..
+            }) = detector.as_mut()
+            else {
+                unreachable!()
+            };
+            if nodes.is_empty() {
+                Self::observe(observer, &document())?;
             }
-            false => Poll::Ready(()),
+            let connected = node.is_connected();
+            if connected {
+                Self::observe_roots(observer, &node)?;
+            }
+            Ok(nodes.insert(BoundNode {
+                node,
+                connected,
+                scope,
+            }))
+        })
+    }
+
+    #[inline]
+    fn unregister(key: usize) {
+        DETECTOR.with(|detector| {
+            if let Some(detector) = detector.borrow_mut().as_mut() {
+                detector.nodes.try_remove(key);
+                if detector.nodes.is_empty() {
+                    detector.observer.disconnect();
+                }
+            }
+        });
+    }
+
+    /// cancel the scopes of bound nodes removed since the last mutation.
+    #[inline]
+    fn check() {
+        let detached = DETECTOR.with(|detector| {
+            let mut detector = detector.borrow_mut();
+            let Some(Detector {
+                observer, nodes, ..
+            }) = detector.as_mut()
+            else {
+                return Vec::new();
+            };
+            let mut detached = Vec::new();
+            for (_, bound) in nodes.iter_mut() {
+                let connected = bound.node.is_connected();
+                if bound.connected && !connected {
+                    detached.extend(bound.scope.upgrade());
+                } else if !bound.connected
+                    && connected
+                    && let Err(err) = Self::observe_roots(observer, &bound.node)
+                {
+                    crate::error!("{err}");
+                }
+                bound.connected = connected;
+            }
+            detached
+        });
+        // cancelling runs cleanups, which unregister
+        for inner in detached {
+            Scope { inner }.cancel(CancelReason::Detached);
         }
     }
 }
 
-impl Drop for ScopeDropFuture {
+/// resolves with the reason once the scope is cancelled or dropped.
+#[derive(Debug)]
+pub struct Cancelled {
+    scope: Weak<RefCell<ScopeInner>>,
+    reason: Rc<RefCell<Option<CancelReason>>>,
+    notified: Notified,
+}
+
+impl Future for Cancelled {
+    type Output = CancelReason;
+
     #[inline]
-    fn drop(&mut self) {
-        if let Some(scope_inner) = self.scope.upgrade() {
-            scope_inner.borrow_mut().wakers.remove(self.waker_index);
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            if let Some(reason) = &*self.reason.borrow() {
+                return Poll::Ready(reason.clone());
+            }
+            let Some(scope) = self.scope.upgrade() else {
+                return Poll::Ready(CancelReason::Dropped);
+            };
+            match Pin::new(&mut self.notified).poll(cx) {
+                Poll::Ready(()) => self.notified = scope.borrow().notify.notified(),
+                Poll::Pending => return Poll::Pending,
+            }
         }
     }
 }
@@ -66,51 +272,156 @@ impl Scope {
         Self::default()
     }
 
+    /// create a nested scope that is cancelled with the same reason when this scope is cancelled or dropped.
+    /// cancelling the child does not affect this scope.
     #[inline]
-    fn make_scope_drop_future(&self) -> ScopeDropFuture {
-        let scope = Rc::downgrade(&self.inner);
-        let waker = Rc::new(RefCell::new(None));
-        let waker_index = self.inner.borrow_mut().wakers.insert(Rc::downgrade(&waker));
-        ScopeDropFuture {
-            waker,
-            scope,
-            waker_index,
+    pub fn child(&self) -> Self {
+        let child = Self::new();
+        let reason = {
+            let mut inner = self.inner.borrow_mut();
+            let cancelled = inner.cancelled.borrow().clone();
+            match cancelled {
+                Some(reason) => Some(reason),
+                None => {
+                    let key = inner.children.insert(Rc::downgrade(&child.inner));
+                    child.inner.borrow_mut().parent = Some((Rc::downgrade(&self.inner), key));
+                    None
+                }
+            }
+        };
+        if let Some(reason) = reason {
+            child.cancel(reason);
         }
+        child
     }
 
+    /// create a scope that is cancelled with `CancelReason::Detached` when `node` is removed from the document.
+    /// if `node` is not connected yet, the scope is cancelled on the first removal after it is connected.
+    /// all bound scopes share one `MutationObserver`, which checks only whether their nodes are still connected.
     #[inline]
-    pub fn wrap_future<F: Future + Unpin>(&self, future: F) -> ScopedFuture<F> {
-        let scope_drop_future = self.make_scope_drop_future();
-        ScopedFuture {
-            scope_drop_future,
-            future,
+    #[track_caller]
+    pub fn bound_to<T: AsRef<web_sys::Node>>(node: &Node<T>) -> Self {
+        let scope = Self::new();
+        let node = AsRef::<web_sys::Node>::as_ref(node).clone();
+        let key = Detector::register(node, Rc::downgrade(&scope.inner)).unwrap_throw();
+        scope.on_cleanup(move || Detector::unregister(key));
+        scope
+    }
+
+    /// cancel this scope and its children while handles may still be alive.
+    /// only the first reason is kept.
+    #[inline]
+    pub fn cancel(&self, reason: impl Into<CancelReason>) {
+        let reason = reason.into();
+        let (children, cleanups) = {
+            let mut inner = self.inner.borrow_mut();
+            if inner.cancelled.borrow().is_some() {
+                return;
+            }
+            inner.cancelled.replace(Some(reason.clone()));
+            inner.notify.notify_all();
+            (inner.take_children(), std::mem::take(&mut inner.cleanups))
+        };
+        for child in children {
+            Scope { inner: child }.cancel(reason.clone());
         }
+        cleanups.run();
+    }
+
+    /// register `f` to run when this scope is cancelled or dropped.
+    /// callbacks run in reverse registration order, after those of child scopes.
+    /// if the scope is already cancelled, `f` runs immediately.
+    #[inline]
+    pub fn on_cleanup(&self, f: impl FnOnce() + 'static) -> &Self {
+        let mut inner = self.inner.borrow_mut();
+        if inner.cancelled.borrow().is_some() {
+            drop(inner);
+            f();
+        } else {
+            inner.cleanups.0.push(Box::new(f));
+        }
+        self
+    }
+
+    /// an `AbortSignal` aborted when this scope is cancelled or dropped.
+    /// e.g. for `DomEventListenerBuilder::signal`
+    #[inline]
+    #[track_caller]
+    pub fn abort_signal(&self) -> web_sys::AbortSignal {
+        let controller = web_sys::AbortController::new().unwrap_throw();
+        let signal = controller.signal();
+        self.on_cleanup(move || controller.abort());
+        signal
     }
 
+    /// keep `value` alive until this scope is cancelled or dropped. e.g. `DomEventBinding`, `Closure`
     #[inline]
-    pub fn wrap_stream<S: Stream + Unpin>(&self, stream: S) -> ScopedStream<S> {
-        let scope_drop_future = self.make_scope_drop_future();
-        ScopedStream {
-            scope_drop_future,
-            stream,
+    pub fn own<T: 'static>(&self, value: T) -> &Self {
+        self.on_cleanup(move || drop(value))
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.borrow().cancelled.borrow().is_some()
+    }
+
+    #[inline]
+    pub fn cancel_reason(&self) -> Option<CancelReason> {
+        self.inner.borrow().cancelled.borrow().clone()
+    }
+
+    #[inline]
+    pub fn cancelled(&self) -> Cancelled {
+        let inner = self.inner.borrow();
+        Cancelled {
+            scope: Rc::downgrade(&self.inner),
+            reason: inner.cancelled.clone(),
+            notified: inner.notify.notified(),
         }
     }
+
+    #[inline]
+    pub fn wrap_future<F: Future>(&self, future: F) -> ScopedFuture<F> {
+        let cancelled = self.cancelled();
+        ScopedFuture { cancelled, future }
+    }
+
+    #[inline]
+    pub fn wrap_stream<S: Stream>(&self, stream: S) -> ScopedStream<S> {
+        let cancelled = self.cancelled();
+        ScopedStream { cancelled, stream }
+    }
+
+    /// spawn `future` as a task owned by this scope. the task is aborted when the scope is cancelled or dropped.
+    /// dropping the `JoinHandle` detaches the task.
+    #[inline]
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        crate::task::spawn(self.wrap_future(future))
+    }
 }
 
-#[derive(Debug)]
-pub struct ScopedFuture<F: Future> {
-    scope_drop_future: ScopeDropFuture,
-    future: F,
+pin_project_lite::pin_project! {
+    #[derive(Debug)]
+    pub struct ScopedFuture<F> {
+        cancelled: Cancelled,
+        #[pin]
+        future: F,
+    }
 }
 
-impl<F: Future + Unpin> Future for ScopedFuture<F> {
+impl<F: Future> Future for ScopedFuture<F> {
     type Output = Option<F::Output>;
 
     #[inline]
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match self.scope_drop_future.poll(cx) {
-            Poll::Ready(()) => Poll::Ready(None),
-            Poll::Pending => match self.future.poll(cx) {
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let proj = self.project();
+        match Pin::new(proj.cancelled).poll(cx) {
+            Poll::Ready(_) => Poll::Ready(None),
+            Poll::Pending => match proj.future.poll(cx) {
                 Poll::Ready(output) => Poll::Ready(Some(output)),
                 Poll::Pending => Poll::Pending,
             },
@@ -118,20 +429,24 @@ impl<F: Future + Unpin> Future for ScopedFuture<F> {
     }
 }
 
-#[derive(Debug)]
-pub struct ScopedStream<S: Stream> {
-    scope_drop_future: ScopeDropFuture,
-    stream: S,
+pin_project_lite::pin_project! {
+    #[derive(Debug)]
+    pub struct ScopedStream<S> {
+        cancelled: Cancelled,
+        #[pin]
+        stream: S,
+    }
 }
 
-impl<S: Stream + Unpin> Stream for ScopedStream<S> {
+impl<S: Stream> Stream for ScopedStream<S> {
     type Item = S::Item;
 
     #[inline]
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.scope_drop_future.poll(cx) {
-            Poll::Ready(()) => Poll::Ready(None),
-            Poll::Pending => self.stream.poll_next(cx),
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let proj = self.project();
+        match Pin::new(proj.cancelled).poll(cx) {
+            Poll::Ready(_) => Poll::Ready(None),
+            Poll::Pending => proj.stream.poll_next(cx),
         }
     }
 }
@@ -161,4 +476,161 @@ mod test {
         assert_eq!(pending.await, None);
         assert_eq!(stream.next().await, None);
     }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn cancel() {
+        let scope = Scope::new();
+        let pending = scope.wrap_future(std::future::pending::<i32>());
+        let mut stream = scope.wrap_stream(futures_lite::stream::repeat(1));
+        let cancelled = scope.cancelled();
+
+        assert_eq!(stream.next().await, Some(1));
+        assert!(!scope.is_cancelled());
+        scope.cancel("abort");
+        scope.cancel("ignored");
+        assert!(scope.is_cancelled());
+        assert_eq!(scope.cancel_reason(), Some("abort".into()));
+        assert_eq!(cancelled.await, CancelReason::from("abort"));
+        assert_eq!(pending.await, None);
+        assert_eq!(stream.next().await, None);
+        assert_eq!(scope.wrap_future(std::future::ready(1)).await, None);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn child() {
+        let parent = Scope::new();
+        let child = parent.child();
+        let grandchild = child.child();
+        let pending = grandchild.wrap_future(std::future::pending::<i32>());
+
+        child.cancel("navigation");
+        assert!(!parent.is_cancelled());
+        assert_eq!(
+            grandchild.cancelled().await,
+            CancelReason::from("navigation")
+        );
+        assert_eq!(pending.await, None);
+
+        let child = parent.child();
+        drop(parent);
+        assert_eq!(child.cancelled().await, CancelReason::Dropped);
+
+        let parent = Scope::new();
+        parent.cancel("abort");
+        assert_eq!(parent.child().cancel_reason(), Some("abort".into()));
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn spawn() {
+        let scope = Scope::new();
+        let handle = scope.spawn(async { 1 });
+        assert_eq!(handle.await, Some(1));
+
+        let handle = scope.spawn(std::future::pending::<i32>());
+        handle.abort();
+        assert_eq!(handle.await, None);
+
+        let (tx, mut rx) = crate::chan::<i32>(usize::MAX);
+        let handle = scope.spawn(async move {
+            let _tx = tx;
+            std::future::pending::<()>().await
+        });
+        assert!(!handle.is_finished());
+        drop(scope);
+        assert_eq!(handle.await, None);
+        // the future itself is dropped as well
+        assert_eq!(rx.next().await, None);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn cleanup() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let push = |n: i32| {
+            let log = log.clone();
+            move || log.borrow_mut().push(n)
+        };
+
+        let scope = Scope::new();
+        let (tx, mut rx) = crate::chan::<i32>(usize::MAX);
+        scope.on_cleanup(push(1)).own(tx).on_cleanup(push(2));
+        let child = scope.child();
+        child.on_cleanup(push(3));
+        drop(scope);
+        assert_eq!(*log.borrow(), [3, 2, 1]);
+        assert!(child.is_cancelled());
+        assert_eq!(rx.next().await, None);
+
+        log.borrow_mut().clear();
+        let scope = Scope::new();
+        let child = scope.child();
+        child.on_cleanup(push(1));
+        scope.on_cleanup(push(2));
+        scope.cancel("abort");
+        assert_eq!(*log.borrow(), [1, 2]);
+        scope.on_cleanup(push(3));
+        assert_eq!(*log.borrow(), [1, 2, 3]);
+        drop(child);
+        drop(scope);
+        assert_eq!(*log.borrow(), [1, 2, 3]);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn bound_to() {
+        let body = crate::body();
+        let connected = Node(document().create_element("div").unwrap());
+        body.append_child(&connected).unwrap();
+        let pending = Node(document().create_element("div").unwrap());
+        let first = Scope::bound_to(&connected);
+        let second = Scope::bound_to(&connected);
+        let later = Scope::bound_to(&pending);
+
+        connected.remove();
+        assert_eq!(first.cancelled().await, CancelReason::Detached);
+        assert_eq!(second.cancel_reason(), Some(CancelReason::Detached));
+        assert!(!later.is_cancelled());
+
+        // not cancelled before the node is connected
+        body.append_child(&pending).unwrap();
+        crate::Timeout::new(0).await;
+        assert!(!later.is_cancelled());
+        pending.remove();
+        assert_eq!(later.cancelled().await, CancelReason::Detached);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    async fn drop_order() {
+        // Cancelled dropped before the scope
+        let scope = Scope::new();
+        let clone = scope.clone();
+        drop(scope.cancelled());
+        drop(scope.wrap_future(std::future::pending::<()>()));
+        drop(scope);
+        drop(clone);
+
+        // scope dropped before Cancelled, one clone at a time
+        let scope = Scope::new();
+        let clone = scope.clone();
+        let mut cancelled = scope.cancelled();
+        drop(scope);
+        assert!(
+            futures_lite::future::poll_once(&mut cancelled)
+                .await
+                .is_none()
+        );
+        drop(clone);
+        assert_eq!(cancelled.await, CancelReason::Dropped);
+
+        // cancelled and then dropped
+        let scope = Scope::new();
+        let cancelled = scope.cancelled();
+        scope.cancel("abort");
+        drop(scope);
+        assert_eq!(cancelled.await, CancelReason::from("abort"));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::DomError;
 use wasm_bindgen::prelude::*;
 
 #[derive(Debug, Clone)]
@@ -6,17 +7,33 @@ pub struct Storage(pub(crate) web_sys::Storage);
 impl Storage {
     #[inline]
     pub fn get(&self, key: &str) -> Option<String> {
-        self.0.get_item(key).unwrap_throw()
+        self.try_get(key).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_get(&self, key: &str) -> Result<Option<String>, DomError> {
+        Ok(self.0.get_item(key)?)
     }
 
     #[inline]
     pub fn set(&self, key: &str, value: &str) {
-        self.0.set_item(key, value).unwrap_throw()
+        self.try_set(key, value).unwrap_throw()
+    }
+
+    /// fails with "QuotaExceededError" when the storage is full.
+    #[inline]
+    pub fn try_set(&self, key: &str, value: &str) -> Result<(), DomError> {
+        Ok(self.0.set_item(key, value)?)
     }
 
     #[inline]
     pub fn remove(&self, key: &str) {
-        self.0.remove_item(key).unwrap_throw()
+        self.try_remove(key).unwrap_throw()
+    }
+
+    #[inline]
+    pub fn try_remove(&self, key: &str) -> Result<(), DomError> {
+        Ok(self.0.remove_item(key)?)
     }
 
     #[inline]
@@ -25,4 +42,15 @@ impl Storage {
         let len = storage.length().unwrap_throw();
         (0..len).filter_map(move |idx| storage.key(idx).unwrap_throw())
     }
+
+    /// reads every key up front, so the first failing key is reported.
+    #[inline]
+    pub fn try_keys(&self) -> Result<impl Iterator<Item = String> + use<>, DomError> {
+        let len = self.0.length()?;
+        let mut keys = Vec::with_capacity(len as usize);
+        for idx in 0..len {
+            keys.extend(self.0.key(idx)?);
+        }
+        Ok(keys.into_iter())
+    }
 }
@@ -0,0 +1,128 @@
+use futures_lite::prelude::*;
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use wasm_bindgen_futures::spawn_local;
+
+struct JoinState<T> {
+    output: Option<T>,
+    finished: bool,
+    aborted: bool,
+    task_waker: Option<Waker>,
+    join_waker: Option<Waker>,
+}
+
+/// resolves to `None` when the task was aborted, either by `abort()` or by its scope.
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<JoinState<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// drop the task's future on its next wake up. no-op if the task has finished.
+    #[inline]
+    pub fn abort(&self) {
+        let waker = {
+            let mut state = self.state.borrow_mut();
+            if state.finished {
+                return;
+            }
+            state.aborted = true;
+            state.task_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().finished
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Option<T>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.borrow_mut();
+        if let Some(output) = state.output.take() {
+            return Poll::Ready(Some(output));
+        }
+        if state.finished {
+            return Poll::Ready(None);
+        }
+        state.join_waker.replace(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+struct Task<T> {
+    future: Option<Pin<Box<dyn Future<Output = Option<T>>>>>,
+    state: Rc<RefCell<JoinState<T>>>,
+}
+
+impl<T> Task<T> {
+    #[inline]
+    fn finish(&mut self, output: Option<T>) {
+        self.future.take();
+        let waker = {
+            let mut state = self.state.borrow_mut();
+            state.output = output;
+            state.finished = true;
+            state.task_waker.take();
+            state.join_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Task<T> {
+    type Output = ();
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.state.borrow().aborted {
+            self.finish(None);
+            return Poll::Ready(());
+        }
+        let Some(future) = self.future.as_mut() else {
+            return Poll::Ready(());
+        };
+        match future.as_mut().poll(cx) {
+            Poll::Ready(output) => {
+                self.finish(output);
+                Poll::Ready(())
+            }
+            Poll::Pending => {
+                self.state
+                    .borrow_mut()
+                    .task_waker
+                    .replace(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// spawn a future resolving to `None` when cancelled. see `Scope::spawn`.
+#[inline]
+pub(crate) fn spawn<T: 'static>(
+    future: impl Future<Output = Option<T>> + 'static,
+) -> JoinHandle<T> {
+    let state = Rc::new(RefCell::new(JoinState {
+        output: None,
+        finished: false,
+        aborted: false,
+        task_waker: None,
+        join_waker: None,
+    }));
+    spawn_local(Task {
+        future: Some(Box::pin(future)),
+        state: state.clone(),
+    });
+    JoinHandle { state }
+}
@@ -0,0 +1,471 @@
+use crate::event::{DomEventStream, PopStateEvent};
+use crate::{BufferPolicy, DomError, Emitter, on_window};
+use futures_lite::prelude::*;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use wasm_bindgen::prelude::*;
+
+/// encode everything except unreserved characters (`A-Z a-z 0-9 - . _ ~`),
+/// so the result is safe in a path segment, query key or value, and fragment.
+#[inline]
+pub fn percent_encode(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// decode `%XX` escapes. invalid escapes are kept as they are, invalid UTF-8 is replaced.
+#[inline]
+pub fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |offset: usize| {
+            bytes
+                .get(i + offset)
+                .and_then(|byte| (*byte as char).to_digit(16))
+        };
+        match (bytes[i], hex(1), hex(2)) {
+            (b'%', Some(high), Some(low)) => {
+                decoded.push((high * 16 + low) as u8);
+                i += 3;
+            }
+            (byte, ..) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// "/" followed by the encoded segments, e.g. `["users", "a b"]` into "/users/a%20b".
+#[inline]
+pub fn build_path<S: AsRef<str>>(segments: impl IntoIterator<Item = S>) -> String {
+    let path = segments
+        .into_iter()
+        .map(|segment| percent_encode(segment.as_ref()))
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("/{path}")
+}
+
+/// decoded non-empty segments of a path, e.g. "/users/a%20b/" into `["users", "a b"]`.
+#[inline]
+pub fn path_segments(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(percent_decode)
+        .collect()
+}
+
+/// ordered query parameters. keys may repeat.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query(Vec<(String, String)>);
+
+impl Query {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// parse "a=1&b=x%20y", with or without a leading "?". "+" decodes to a space.
+    #[inline]
+    pub fn parse(query: &str) -> Self {
+        let query = query.strip_prefix('?').unwrap_or(query);
+        let decode = |part: &str| percent_decode(&part.replace('+', " "));
+        let pairs = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => (decode(key), decode(value)),
+                None => (decode(pair), String::new()),
+            })
+            .collect();
+        Self(pairs)
+    }
+
+    /// the query of the current location.
+    #[inline]
+    pub fn current() -> Self {
+        let search = crate::try_location()
+            .and_then(|location| location.search().ok())
+            .unwrap_or_default();
+        Self::parse(&search)
+    }
+
+    /// the first value of `key`.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// e.g. `query.get_as::<u32>("page")`. `None` if missing or unparsable.
+    #[inline]
+    pub fn get_as<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key)?.parse().ok()
+    }
+
+    #[inline]
+    pub fn get_all(&self, key: &str) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// replace every value of `key`, keeping the position of the first one.
+    #[inline]
+    pub fn set(&mut self, key: impl Into<String>, value: impl ToString) -> &mut Self {
+        let key = key.into();
+        let value = value.to_string();
+        match self.0.iter().position(|(k, _)| *k == key) {
+            Some(index) => {
+                self.0[index].1 = value;
+                let mut position = 0;
+                self.0.retain(|(k, _)| {
+                    let keep = position <= index || *k != key;
+                    position += 1;
+                    keep
+                });
+            }
+            None => self.0.push((key, value)),
+        }
+        self
+    }
+
+    #[inline]
+    pub fn append(&mut self, key: impl Into<String>, value: impl ToString) -> &mut Self {
+        self.0.push((key.into(), value.to_string()));
+        self
+    }
+
+    #[inline]
+    pub fn remove(&mut self, key: &str) -> &mut Self {
+        self.0.retain(|(k, _)| k != key);
+        self
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+/// encoded, without a leading "?".
+impl std::fmt::Display for Query {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (key, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "&")?;
+            }
+            write!(f, "{}={}", percent_encode(key), percent_encode(value))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Query {
+    type Err = std::convert::Infallible;
+
+    #[inline]
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(query))
+    }
+}
+
+impl<K: Into<String>, V: ToString> FromIterator<(K, V)> for Query {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.to_string()))
+                .collect(),
+        )
+    }
+}
+
+/// the rest of `url` after a scheme such as "https:", which must come before any '/', '?' or '#'.
+#[inline]
+fn strip_scheme(url: &str) -> Option<&str> {
+    let index = url.find([':', '/', '?', '#'])?;
+    let (scheme, rest) = url.split_at(index);
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    match valid {
+        true => rest.strip_prefix(':'),
+        false => None,
+    }
+}
+
+/// a same-origin URL: path, query and fragment. `path` and `fragment` are kept encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Url {
+    pub path: String,
+    pub query: Query,
+    pub fragment: Option<String>,
+}
+
+impl Url {
+    /// parse "/path?query#fragment". scheme and host of absolute URLs are dropped.
+    #[inline]
+    pub fn parse(url: &str) -> Self {
+        let (url, absolute) = match strip_scheme(url) {
+            Some(rest) => match rest.strip_prefix("//") {
+                Some(rest) => (
+                    rest.find(['/', '?', '#'])
+                        .map_or("", |index| &rest[index..]),
+                    true,
+                ),
+                None => (rest, true),
+            },
+            None => (url, false),
+        };
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment.to_string())),
+            None => (url, None),
+        };
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        Self {
+            path: match path.is_empty() && absolute {
+                true => "/".into(),
+                false => path.into(),
+            },
+            query: Query::parse(query),
+            fragment,
+        }
+    }
+
+    /// the current location.
+    #[inline]
+    pub fn current() -> Self {
+        let Some(location) = crate::try_location() else {
+            return Self::default();
+        };
+        let fragment = location.hash().unwrap_or_default();
+        Self {
+            path: location.pathname().unwrap_or_default(),
+            query: Query::parse(&location.search().unwrap_or_default()),
+            fragment: fragment.strip_prefix('#').map(Into::into),
+        }
+    }
+
+    #[inline]
+    pub fn segments(&self) -> Vec<String> {
+        path_segments(&self.path)
+    }
+}
+
+impl std::fmt::Display for Url {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path)?;
+        if !self.query.is_empty() {
+            write!(f, "?{}", self.query)?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{fragment}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Url {
+    type Err = std::convert::Infallible;
+
+    #[inline]
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(url))
+    }
+}
+
+thread_local! {
+    static LOCATION_CHANGED: Emitter<()> = Emitter::new(usize::MAX);
+}
+
+/// let query watchers know that the location changed through `pushState` or `replaceState`,
+/// which fire no event.
+#[inline]
+pub(crate) fn location_changed() {
+    LOCATION_CHANGED.with(|emitter| emitter.emit(()));
+}
+
+/// replace the query of the current history entry without reloading. path, fragment and state are kept.
+#[inline]
+#[track_caller]
+pub fn set_query(query: &Query) {
+    try_set_query(query).unwrap_throw()
+}
+
+#[inline]
+pub fn try_set_query(query: &Query) -> Result<(), DomError> {
+    let history = crate::history();
+    let url = Url {
+        query: query.clone(),
+        ..Url::current()
+    };
+    history.replace_state_with_url(&history.state()?, "", Some(&url.to_string()))?;
+    location_changed();
+    Ok(())
+}
+
+/// like `set_query`, but push a history entry.
+#[inline]
+#[track_caller]
+pub fn push_query(query: &Query) {
+    try_push_query(query).unwrap_throw()
+}
+
+#[inline]
+pub fn try_push_query(query: &Query) -> Result<(), DomError> {
+    let url = Url {
+        query: query.clone(),
+        ..Url::current()
+    };
+    crate::history().push_state_with_url(&JsValue::NULL, "", Some(&url.to_string()))?;
+    location_changed();
+    Ok(())
+}
+
+/// set or remove (`None`) one query parameter with `set_query`.
+#[inline]
+#[track_caller]
+pub fn set_query_param(key: &str, value: Option<&str>) {
+    let mut query = Query::current();
+    match value {
+        Some(value) => query.set(key, value),
+        None => query.remove(key),
+    };
+    set_query(&query)
+}
+
+/// yields the value of query parameter `key` now, and whenever it changes on back/forward,
+/// `set_query` or router navigation.
+#[inline]
+pub fn watch_query_param(key: impl Into<String>) -> QueryParamStream {
+    QueryParamStream {
+        key: key.into(),
+        last: None,
+        popstate: on_window::<PopStateEvent>().bind_stream(
+            1,
+            BufferPolicy::Latest,
+            |_| Some(()),
+            |_, ()| (),
+        ),
+        changes: Box::pin(LOCATION_CHANGED.with(Emitter::receive)),
+    }
+}
+
+pub struct QueryParamStream {
+    key: String,
+    // `None` before the first value
+    last: Option<Option<String>>,
+    popstate: DomEventStream<PopStateEvent, ()>,
+    changes: Pin<Box<dyn Stream<Item = ()>>>,
+}
+
+impl Stream for QueryParamStream {
+    type Item = Option<String>;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let mut changed = this.last.is_none();
+        loop {
+            let popstate = this.popstate.poll_next(cx);
+            let changes = this.changes.as_mut().poll_next(cx);
+            match (popstate, changes) {
+                (Poll::Ready(None), _) | (_, Poll::Ready(None)) => return Poll::Ready(None),
+                (Poll::Ready(Some(())), _) | (_, Poll::Ready(Some(()))) => changed = true,
+                (Poll::Pending, Poll::Pending) => break,
+            }
+        }
+        if changed {
+            let value = Query::current().get(&this.key).map(Into::into);
+            if this.last.as_ref() != Some(&value) {
+                this.last = Some(value.clone());
+                return Poll::Ready(Some(value));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn encoding() {
+        assert_eq!(percent_encode("a b/c?d=é"), "a%20b%2Fc%3Fd%3D%C3%A9");
+        assert_eq!(percent_decode("a%20b%2Fc%3Fd%3D%C3%A9"), "a b/c?d=é");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz+"), "%zz+");
+        assert_eq!(build_path(["users", "a b"]), "/users/a%20b");
+        assert_eq!(path_segments("/users/a%20b/"), ["users", "a b"]);
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn query() {
+        let mut query = Query::parse("?q=hello+world&tag=a&tag=b&page=2&flag");
+        assert_eq!(query.get("q"), Some("hello world"));
+        assert_eq!(query.get_all("tag").collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(query.get_as::<u32>("page"), Some(2));
+        assert_eq!(query.get("flag"), Some(""));
+        assert_eq!(query.get("missing"), None);
+
+        query.set("tag", "c").set("page", 3).remove("flag");
+        assert_eq!(query.to_string(), "q=hello%20world&tag=c&page=3");
+        assert_eq!(Query::parse(&query.to_string()), query);
+
+        let query = [("a", 1), ("b", 2)].into_iter().collect::<Query>();
+        assert_eq!(query.to_string(), "a=1&b=2");
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(dead_code)]
+    fn url() {
+        let url = Url::parse("https://example.com/users/a%20b?tab=posts#top");
+        assert_eq!(url.path, "/users/a%20b");
+        assert_eq!(url.segments(), ["users", "a b"]);
+        assert_eq!(url.query.get("tab"), Some("posts"));
+        assert_eq!(url.fragment.as_deref(), Some("top"));
+        assert_eq!(url.to_string(), "/users/a%20b?tab=posts#top");
+
+        assert_eq!(Url::parse("https://example.com").path, "/");
+        assert_eq!(Url::parse("https://example.com?a=1").to_string(), "/?a=1");
+        assert_eq!(Url::parse("/search?").to_string(), "/search");
+
+        // a URL in the query of a relative one
+        let url = Url::parse("/login?next=https://a.com/b");
+        assert_eq!(url.path, "/login");
+        assert_eq!(url.query.get("next"), Some("https://a.com/b"));
+        assert_eq!(Url::parse("users?next=x:y").path, "users");
+        assert_eq!(Url::parse("HTTP+x://host/a").path, "/a");
+    }
+}